@@ -1,7 +1,7 @@
 from __future__ import annotations
 from typing import Iterable, Optional
 
-from dpa_core import filter_py, select_py, convert_py, profile_py
+from dpa_core import filter_py, select_py, convert_py, profile_py, sample_py, split_py, kfold_py, validate_py
 
 def _maybe_load(path: str, as_pandas: bool = False, as_polars: bool = False):
     if as_pandas:
@@ -27,3 +27,22 @@ def convert(input: str, output: str):
 
 def profile(input: str) -> dict:
     return dict(profile_py(str(input)))
+
+def sample(input: str, size: Optional[int] = None, seed: Optional[int] = None, stratify: Optional[str] = None,
+           output: Optional[str] = None, *, fraction: Optional[float] = None, as_pandas=False, as_polars=False):
+    out = sample_py(str(input), int(size) if size is not None else None, seed, stratify, output,
+                    float(fraction) if fraction is not None else None)
+    return _maybe_load(out, as_pandas=as_pandas, as_polars=as_polars)
+
+def split(input: str, train_output: str, test_output: str, test_size: float = 0.2,
+          seed: Optional[int] = None, stratify: Optional[str] = None):
+    return split_py(str(input), str(train_output), str(test_output), float(test_size), seed, stratify)
+
+def kfold(input: str, output_dir: str, folds: int = 5, seed: Optional[int] = None,
+          stratify: Optional[str] = None) -> list:
+    return kfold_py(str(input), str(output_dir), int(folds), seed, stratify)
+
+def validate(input: str, schema: Optional[str] = None, not_null: Optional[Iterable[str]] = None,
+             unique: Optional[Iterable[str]] = None) -> list:
+    return validate_py(str(input), schema, list(not_null) if not_null else None,
+                       list(unique) if unique else None)
@@ -1,48 +1,724 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command, value_parser};
+
+// Accepts a single ASCII character, plus `\t`/`tab` for tab-separated files.
+fn parse_byte(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("expected a single ASCII character, got {s:?}")),
+    }
+}
+
+// Accepts plain byte counts or KB/MB/GB suffixes (binary multiples), e.g. 256MB.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let t = s.trim().to_ascii_uppercase();
+    let t = t.strip_suffix('B').unwrap_or(&t);
+    let (num, mult) = match t.char_indices().last() {
+        Some((i, 'K')) => (&t[..i], 1u64 << 10),
+        Some((i, 'M')) => (&t[..i], 1u64 << 20),
+        Some((i, 'G')) => (&t[..i], 1u64 << 30),
+        _ => (t, 1),
+    };
+    num.trim().parse::<u64>()
+        .map(|n| n * mult)
+        .map_err(|_| format!("expected a size like 512MB or 1GB, got {s:?}"))
+}
 
 pub fn build_cli() -> Command {
     Command::new("dpa")
         .about("Data Processing Accelerator (Rust + Polars)")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(Arg::new("input-format").long("input-format").global(true)
+            .value_parser(["csv", "parquet", "pq", "json", "jsonl", "xml"])
+            .help("Read inputs as this format instead of inferring from the extension"))
+        .arg(Arg::new("output-format").long("output-format").global(true)
+            .value_parser(["csv", "parquet", "pq", "jsonl", "ndjson", "xlsx"])
+            .help("Write outputs as this format instead of inferring from the extension"))
+        .arg(Arg::new("delimiter").long("delimiter").global(true)
+            .value_parser(parse_byte)
+            .help("CSV input field separator (default: ,)"))
+        .arg(Arg::new("no-header").long("no-header").global(true)
+            .action(ArgAction::SetTrue)
+            .help("CSV input has no header row"))
+        .arg(Arg::new("skip-rows").long("skip-rows").global(true)
+            .value_parser(value_parser!(usize))
+            .help("Skip this many lines at the start of CSV input"))
+        .arg(Arg::new("null-values").long("null-values").global(true)
+            .help("Comma-separated tokens read as null in CSV input, e.g. NA,-"))
+        .arg(Arg::new("quote-char").long("quote-char").global(true)
+            .value_parser(parse_byte)
+            .help("CSV input quote character (default: \")"))
+        .arg(Arg::new("encoding").long("encoding").global(true)
+            .value_parser(["utf8", "latin1", "windows-1252", "auto"])
+            .help("CSV input text encoding; `auto` detects UTF-8 vs legacy code pages"))
+        .arg(Arg::new("on-bad-lines").long("on-bad-lines").global(true)
+            .value_parser(["error", "skip", "warn"])
+            .help("What to do with CSV rows whose field count doesn't match the header (default: error)"))
+        .arg(Arg::new("bad-lines-output").long("bad-lines-output").global(true)
+            .help("Write skipped malformed CSV rows to this file"))
+        .arg(Arg::new("dtypes").long("dtypes").global(true)
+            .help("Force column types on read, e.g. id=Int64,zip=Utf8"))
+        .arg(Arg::new("schema-file").long("schema-file").global(true)
+            .help("JSON object mapping column names to dtypes to force on read"))
+        .arg(Arg::new("xml-record-tag").long("xml-record-tag").global(true)
+            .help("XML element that delimits one record (default: children of the root)"))
+        .arg(Arg::new("output-delimiter").long("output-delimiter").global(true)
+            .value_parser(parse_byte)
+            .help("CSV output field separator (default: ,)"))
+        .arg(Arg::new("output-no-header").long("output-no-header").global(true)
+            .action(ArgAction::SetTrue)
+            .help("Omit the header row from CSV output"))
+        .arg(Arg::new("output-quote-style").long("output-quote-style").global(true)
+            .value_parser(["necessary", "always", "non-numeric", "never"])
+            .help("When to quote CSV output fields (default: necessary)"))
+        .arg(Arg::new("output-null").long("output-null").global(true)
+            .help("String written for nulls in CSV output (default: empty)"))
+        .arg(Arg::new("compression").long("compression").global(true)
+            .value_parser(["zstd", "snappy", "lz4", "uncompressed"])
+            .help("Parquet output codec (default: zstd)"))
+        .arg(Arg::new("compression-level").long("compression-level").global(true)
+            .value_parser(value_parser!(i32))
+            .help("Zstd compression level for Parquet output"))
+        .arg(Arg::new("row-group-size").long("row-group-size").global(true)
+            .value_parser(value_parser!(usize))
+            .help("Rows per Parquet row group"))
+        .arg(Arg::new("no-statistics").long("no-statistics").global(true)
+            .action(ArgAction::SetTrue)
+            .help("Skip writing Parquet column statistics"))
+        .arg(Arg::new("force").long("force").global(true)
+            .action(ArgAction::SetTrue)
+            .help("Overwrite output files that already exist"))
+        .arg(Arg::new("max-rows-per-file").long("max-rows-per-file").global(true)
+            .value_parser(value_parser!(usize))
+            .help("Write the output as a directory of part files with at most this many rows each"))
+        .arg(Arg::new("max-file-size").long("max-file-size").global(true)
+            .value_parser(parse_size)
+            .help("Write the output as a directory of part files of roughly this size, e.g. 512MB"))
+        .arg(Arg::new("sheet-name").long("sheet-name").global(true)
+            .help("Worksheet name for .xlsx outputs (default: Sheet1)"))
         .subcommand(Command::new("schema")
             .about("Print schema of a file")
-            .arg(Arg::new("input").required(true)))
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("format").long("format").default_value("text")
+                .value_parser(["text", "json", "yaml"]))
+            .arg(Arg::new("save").long("save")
+                .help("Also write the schema as JSON, loadable via --schema-file or validate --schema")))
         .subcommand(Command::new("head")
             .about("Preview first N rows")
             .arg(Arg::new("input").required(true))
-            .arg(Arg::new("n").short('n').long("n").default_value("10")))
+            .arg(Arg::new("n").short('n').long("n").default_value("10"))
+            .arg(Arg::new("format").long("format").default_value("table")
+                .value_parser(["table", "csv", "json", "markdown"]))
+            .arg(Arg::new("max-col-width").long("max-col-width").value_parser(value_parser!(usize))
+                .help("Truncate long values in table and markdown output")))
+        .subcommand(Command::new("tail")
+            .about("Preview last N rows")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("n").short('n').long("n").default_value("10"))
+            .arg(Arg::new("format").long("format").default_value("table")
+                .value_parser(["table", "csv", "json", "markdown"]))
+            .arg(Arg::new("max-col-width").long("max-col-width").value_parser(value_parser!(usize))
+                .help("Truncate long values in table and markdown output")))
         .subcommand(Command::new("filter").alias("f")
             .about("Filter rows with an expression and (optionally) select columns")
             .arg(Arg::new("input").required(true))
-            .arg(Arg::new("where").short('w').long("where").required(true))
+            .arg(Arg::new("where").short('w').long("where").action(ArgAction::Append)
+                .required_unless_present("or-where")
+                .help("SQL predicate; repeat to AND several together"))
+            .arg(Arg::new("or-where").long("or-where").action(ArgAction::Append)
+                .help("Alternative predicates; a row must match at least one of them"))
+            .arg(Arg::new("offset").long("offset").value_parser(value_parser!(usize))
+                .help("Skip this many matching rows"))
+            .arg(Arg::new("limit").long("limit").value_parser(value_parser!(usize))
+                .help("Keep at most this many matching rows"))
             .arg(Arg::new("select").short('s').long("select").required(false))
-            .arg(Arg::new("output").short('o').long("output").required(true)))
+            .arg(Arg::new("output").short('o').long("output").required(true))
+            .arg(Arg::new("append").long("append").action(ArgAction::SetTrue)
+                .help("Append to an existing output file or Parquet dataset directory")))
         .subcommand(Command::new("select").alias("s")
             .about("Select columns")
             .arg(Arg::new("input").required(true))
-            .arg(Arg::new("columns").short('c').long("columns").required(true))
+            .arg(Arg::new("columns").short('c').long("columns")
+                .required_unless_present_any(["exclude", "dtype"])
+                .help("Names, old:new renames, globs (sales_*) or regexes (re:^f[0-9]+$), comma-separated, in output order"))
+            .arg(Arg::new("exclude").long("exclude")
+                .help("Columns to leave out; accepts the same patterns as --columns"))
+            .arg(Arg::new("dtype").long("dtype")
+                .value_parser(["numeric", "string", "temporal", "boolean"])
+                .help("Keep only columns of this kind"))
             .arg(Arg::new("output").short('o').long("output").required(true)))
         .subcommand(Command::new("convert").alias("c")
             .about("Convert between CSV and Parquet")
             .arg(Arg::new("input").required(true))
-            .arg(Arg::new("output").required(true)))
+            .arg(Arg::new("output").required(true))
+            .arg(Arg::new("append").long("append").action(ArgAction::SetTrue)
+                .help("Append to an existing output file or Parquet dataset directory")))
         .subcommand(Command::new("profile").alias("p")
             .about("Simple profile: count, null %, min/max (sampled)")
-            .arg(Arg::new("input").required(true)))
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("format").long("format").default_value("text")
+                .value_parser(["text", "json", "html"])
+                .help("json/html emit the full report: summary, per-column stats, histograms and top values"))
+            .arg(Arg::new("output").short('o').long("output").help("Write the report here instead of stdout")))
         .subcommand(Command::new("agg").alias("a")
             .about("Groupby aggregations")
             .arg(Arg::new("input").required(true))
-            .arg(Arg::new("group").short('g').long("group").required(true))
+            .arg(Arg::new("group").short('g').long("group").action(ArgAction::Append)
+                .required_unless_present("group-by-date")
+                .help("Group-by columns, comma-separated or repeated"))
+            .arg(Arg::new("group-by-date").long("group-by-date").action(ArgAction::Append)
+                .help("Group on a truncated timestamp, e.g. order_ts:month (hour, day, week, month, quarter, year)"))
             .arg(Arg::new("sum").long("sum").num_args(0..))
             .arg(Arg::new("mean").long("mean").num_args(0..))
             .arg(Arg::new("count").long("count").num_args(0..))
+            .arg(Arg::new("min").long("min").num_args(0..))
+            .arg(Arg::new("max").long("max").num_args(0..))
+            .arg(Arg::new("median").long("median").num_args(0..))
+            .arg(Arg::new("std").long("std").num_args(0..))
+            .arg(Arg::new("first").long("first").num_args(0..))
+            .arg(Arg::new("last").long("last").num_args(0..))
+            .arg(Arg::new("n-unique").long("n-unique").num_args(0..))
+            .arg(Arg::new("quantile").long("quantile").num_args(0..)
+                .help("column:q pairs, e.g. amount:0.95 (output column p95_amount)"))
+            .arg(Arg::new("having").long("having")
+                .help("Filter groups after aggregating, e.g. \"sum_amount > 1000\""))
+            .arg(Arg::new("pivot").long("pivot")
+                .help("Spread each value of this column into its own columns, e.g. sum_amount_electronics"))
             .arg(Arg::new("output").short('o').long("output").required(true)))
         .subcommand(Command::new("join").alias("j")
             .about("Join two datasets")
             .arg(Arg::new("left").required(true))
             .arg(Arg::new("right").required(true))
-            .arg(Arg::new("on").long("on").required(true))
-            .arg(Arg::new("how").long("how").default_value("inner"))
+            .arg(Arg::new("on").long("on").action(ArgAction::Append)
+                .help("Key column(s), comma-separated or repeated (not used by cross joins)"))
+            .arg(Arg::new("left-on").long("left-on").action(ArgAction::Append)
+                .conflicts_with("on").requires("right-on")
+                .help("Key column(s) in the left file when names differ"))
+            .arg(Arg::new("right-on").long("right-on").action(ArgAction::Append)
+                .conflicts_with("on").requires("left-on")
+                .help("Key column(s) in the right file, matched to --left-on by position"))
+            .arg(Arg::new("by").long("by").action(ArgAction::Append)
+                .help("asof: columns that must match exactly before the nearest-key search"))
+            .arg(Arg::new("strategy").long("strategy").default_value("backward")
+                .value_parser(["backward", "forward", "nearest"])
+                .help("asof: take the previous, next or closest right row"))
+            .arg(Arg::new("tolerance").long("tolerance")
+                .help("asof: maximum key distance, e.g. 5 or 5m"))
+            .arg(Arg::new("similarity").long("similarity").default_value("0.85")
+                .value_parser(value_parser!(f64))
+                .help("fuzzy: minimum similarity between 0 and 1"))
+            .arg(Arg::new("algorithm").long("algorithm").default_value("jarowinkler")
+                .value_parser(["jarowinkler", "levenshtein"])
+                .help("fuzzy: string similarity measure"))
+            .arg(Arg::new("suffix").long("suffix").default_value("_right")
+                .help("Suffix for right-hand columns whose names clash with the left"))
+            .arg(Arg::new("coalesce").long("coalesce").action(ArgAction::SetTrue)
+                .help("Merge left and right key columns into one (useful for full joins)"))
+            .arg(Arg::new("validate").long("validate")
+                .value_parser(["1:1", "1:m", "m:1", "m:m"])
+                .help("Fail unless keys are unique on the \"1\" side(s) of the relationship"))
+            .arg(Arg::new("broadcast").long("broadcast")
+                .value_parser(["left", "right"])
+                .help("Load this (small) side into memory once and stream the other side"))
+            .arg(Arg::new("how").long("how").default_value("inner")
+                .help("inner, left, full (alias outer), cross, semi, anti, asof or fuzzy"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("dedup").alias("distinct")
+            .about("Drop duplicate rows")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("subset").long("subset").help("Columns that define a duplicate (default: all)"))
+            .arg(Arg::new("keep").long("keep").default_value("first")
+                .value_parser(["first", "last", "none"]))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("sql")
+            .about("Run a SQL query over one or more files")
+            .arg(Arg::new("query").required(true)
+                .help("Query; quoted paths after FROM/JOIN are read as tables, e.g. FROM 'sales.parquet'"))
+            .arg(Arg::new("table").long("table").action(ArgAction::Append)
+                .help("Register a file under a table name, e.g. --table sales=data/sales.parquet"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("pivot")
+            .about("Reshape long data into a wide table")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("index").long("index").required(true))
+            .arg(Arg::new("columns").long("columns").required(true)
+                .help("Column whose distinct values become output columns"))
+            .arg(Arg::new("values").long("values").required(true))
+            .arg(Arg::new("agg").long("agg").default_value("first")
+                .value_parser(["sum", "mean", "median", "min", "max", "count", "first", "last"]))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("melt").alias("unpivot")
+            .about("Reshape a wide table into long format")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("id-vars").long("id-vars").required(true))
+            .arg(Arg::new("value-vars").long("value-vars")
+                .help("Columns to unpivot (default: all non-id columns)"))
+            .arg(Arg::new("variable-name").long("variable-name").default_value("variable"))
+            .arg(Arg::new("value-name").long("value-name").default_value("value"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("concat").alias("union")
+            .about("Stack rows from multiple files")
+            .arg(Arg::new("inputs").required(true).num_args(1..))
+            .arg(Arg::new("how").long("how").default_value("vertical")
+                .value_parser(["vertical", "diagonal"])
+                .help("diagonal unions differing schemas, filling missing columns with nulls"))
+            .arg(Arg::new("source-column").long("source-column")
+                .help("Add a column with each row's source file path"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("diff")
+            .about("Compare two datasets by key: added, removed and changed rows")
+            .arg(Arg::new("old").required(true))
+            .arg(Arg::new("new").required(true))
+            .arg(Arg::new("key").long("key").required(true))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write differing rows here, tagged with a _diff column")))
+        .subcommand(Command::new("describe")
+            .about("Summary statistics per column (count, mean, quartiles, top value, ...)")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the summary table here instead of printing it")))
+        .subcommand(Command::new("count")
+            .about("Count rows, optionally per group")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("by").long("by").help("Group columns for per-group counts"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write per-group counts here instead of printing them")))
+        .subcommand(Command::new("rename")
+            .about("Rename columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("map").long("map").help("Renames as old=new pairs, e.g. cust=customer_id,amt=amount"))
+            .arg(Arg::new("from-file").long("from-file").help("Two-column file of old,new names"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("cast")
+            .about("Convert column dtypes")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("types").long("types").required(true)
+                .help("Target types as col=type pairs, e.g. amount=f64,signup=date"))
+            .arg(Arg::new("date-format").long("date-format")
+                .help("strptime format for string to date/datetime casts, e.g. %d/%m/%Y"))
+            .arg(Arg::new("mode").long("mode").default_value("lenient")
+                .value_parser(["strict", "lenient"])
+                .help("strict fails on unconvertible values; lenient turns them into nulls"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("fillna")
+            .about("Fill missing values")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("strategy").long("strategy").required(true)
+                .value_parser(["mean", "median", "mode", "constant", "forward", "backward"]))
+            .arg(Arg::new("columns").long("columns")
+                .help("Columns to fill (default: all; numeric only for mean/median)"))
+            .arg(Arg::new("value").long("value").help("Fill value for --strategy constant"))
             .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("dropna")
+            .about("Drop rows with missing values")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("subset").long("subset").help("Columns to check (default: all)"))
+            .arg(Arg::new("how").long("how").default_value("any")
+                .value_parser(["any", "all"])
+                .help("Drop rows with any null, or only rows where all checked columns are null"))
+            .arg(Arg::new("thresh").long("thresh").value_parser(value_parser!(usize))
+                .help("Keep rows with at least this many non-null values (overrides --how)"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("replace")
+            .about("Substitute values in one or more columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("map").long("map").required(true)
+                .help("Replacements as from=to pairs; `null` as the target writes a null"))
+            .arg(Arg::new("regex").long("regex").action(ArgAction::SetTrue)
+                .help("Treat the from side as regex patterns matched against each value"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("explode")
+            .about("Expand list columns into one row per element")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("unnest")
+            .about("Flatten struct columns into top-level columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("separator").long("separator").default_value(".")
+                .help("Joins the struct column name and field name"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("window")
+            .about("Rolling and partitioned window aggregates")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("over").long("over").help("Partition columns"))
+            .arg(Arg::new("order-by").long("order-by").help("Sort rows before computing windows"))
+            .arg(Arg::new("rolling").long("rolling").value_parser(value_parser!(usize))
+                .help("Rolling window size in rows; without it aggregates cover the whole partition"))
+            .arg(Arg::new("agg").long("agg").required(true)
+                .help("Aggregations as agg:column, e.g. mean:amount,sum:amount"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("resample")
+            .about("Aggregate time series into regular intervals")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("time").long("time").required(true).help("Datetime/date index column"))
+            .arg(Arg::new("every").long("every").required(true).help("Interval, e.g. 15m, 1h, 1d, 1w"))
+            .arg(Arg::new("agg").long("agg").required(true)
+                .help("Aggregations as agg:column; count:* counts rows per interval"))
+            .arg(Arg::new("by").long("by").help("Resample each group separately"))
+            .arg(Arg::new("fill-gaps").long("fill-gaps").action(ArgAction::SetTrue)
+                .help("Emit empty intervals too (upsampling), with null aggregates"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("bin").alias("cut")
+            .about("Discretize a numeric column into labelled bins")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("breaks").long("breaks").help("Bin edges, e.g. 18,30,50,65 (n edges make n+1 bins)"))
+            .arg(Arg::new("quantiles").long("quantiles").value_parser(value_parser!(usize))
+                .help("Number of equal-frequency bins, as an alternative to --breaks"))
+            .arg(Arg::new("labels").long("labels").help("One label per bin (default: interval notation)"))
+            .arg(Arg::new("left-closed").long("left-closed").action(ArgAction::SetTrue)
+                .help("Bins include their lower edge instead of their upper edge"))
+            .arg(Arg::new("into").long("into").help("Output column (default: <column>_bin)"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("encode")
+            .about("One-hot or label encode categorical columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("method").long("method").default_value("onehot")
+                .value_parser(["onehot", "label"]))
+            .arg(Arg::new("drop-first").long("drop-first").action(ArgAction::SetTrue)
+                .help("Omit the first category's indicator column (onehot only)"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("scale").alias("normalize")
+            .about("Standardize or normalize numeric columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns"))
+            .arg(Arg::new("method").long("method").default_value("zscore")
+                .value_parser(["zscore", "minmax", "robust"]))
+            .arg(Arg::new("params-out").long("params-out")
+                .help("Save the fitted parameters as JSON"))
+            .arg(Arg::new("params-in").long("params-in")
+                .help("Apply previously saved parameters instead of fitting"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("mask").alias("hash")
+            .about("Hash or redact sensitive columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("method").long("method").default_value("sha256")
+                .value_parser(["sha256", "redact", "last4"]))
+            .arg(Arg::new("salt").long("salt").help("Prefix mixed into every sha256 hash"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("extract")
+            .about("Create columns from regex capture groups")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("pattern").long("pattern").required(true))
+            .arg(Arg::new("into").long("into")
+                .help("One output column per capture group (default: <column>_extract)"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("split-col")
+            .about("Split a delimited string column into several columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("by").long("by").required(true).help("Delimiter string"))
+            .arg(Arg::new("into").long("into").required(true)
+                .help("Output columns; the last one receives any remainder"))
+            .arg(Arg::new("types").long("types").help("Dtypes for output columns, e.g. age=int64"))
+            .arg(Arg::new("drop").long("drop").action(ArgAction::SetTrue)
+                .help("Remove the source column"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("concat-col")
+            .about("Join several columns into one string column")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("sep").long("sep").default_value(""))
+            .arg(Arg::new("into").long("into").required(true))
+            .arg(Arg::new("skip-nulls").long("skip-nulls").action(ArgAction::SetTrue)
+                .help("Join the non-null parts instead of producing null when any part is null"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("parse-dates")
+            .about("Parse string columns into Datetime or Date values")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("format").long("format")
+                .help("strftime format, e.g. \"%Y-%m-%d %H:%M\" (default: inferred)"))
+            .arg(Arg::new("tz").long("tz").help("Time zone to attach or convert to, e.g. UTC"))
+            .arg(Arg::new("date").long("date").action(ArgAction::SetTrue)
+                .help("Produce Date instead of Datetime"))
+            .arg(Arg::new("strict").long("strict").action(ArgAction::SetTrue)
+                .help("Fail instead of writing nulls when values cannot be parsed"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("derive").alias("with-column")
+            .about("Add computed columns from SQL expressions")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("expr").long("expr").required(true).action(ArgAction::Append)
+                .help("name = expression, e.g. \"margin = (revenue - cost) / revenue\"; repeatable"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("top")
+            .about("Keep the k largest (or smallest) rows, optionally per group")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("by").long("by").required(true))
+            .arg(Arg::new("k").short('k').long("k").default_value("10").value_parser(value_parser!(usize)))
+            .arg(Arg::new("per").long("per").help("Take the top k within each group"))
+            .arg(Arg::new("smallest").long("smallest").action(ArgAction::SetTrue)
+                .help("Keep the smallest values instead of the largest"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("crosstab")
+            .about("Contingency table of counts or percentages")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("rows").long("rows").required(true))
+            .arg(Arg::new("cols").long("cols").required(true))
+            .arg(Arg::new("normalize").long("normalize").value_parser(["row", "col", "all"])
+                .help("Show percentages of the row, column or grand total instead of counts"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("corr")
+            .about("Pairwise correlation matrix of numeric columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").help("Columns to correlate (default: all numeric)"))
+            .arg(Arg::new("method").long("method").default_value("pearson")
+                .value_parser(["pearson", "spearman"]))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("value-counts")
+            .about("Frequency table of values in one or more columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true)
+                .help("Column, or comma-separated columns stacked as column/value rows"))
+            .arg(Arg::new("top").long("top").value_parser(value_parser!(usize))
+                .help("Only keep the most frequent values"))
+            .arg(Arg::new("normalize").long("normalize").action(ArgAction::SetTrue)
+                .help("Add each value's share of all rows"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("shuffle")
+            .about("Randomly permute all rows")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("seed").long("seed").value_parser(value_parser!(u64))
+                .help("Seed for a reproducible order"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("transpose")
+            .about("Swap rows and columns of a small table")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("header-column").long("header-column")
+                .help("Column whose values name the transposed columns"))
+            .arg(Arg::new("names-as").long("names-as").default_value("column")
+                .help("Name of the column holding the original column names"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("add-index")
+            .about("Prepend a monotonically increasing row id column")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("name").long("name").default_value("row_id"))
+            .arg(Arg::new("start").long("start").default_value("0").value_parser(value_parser!(u32)))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("clip").alias("winsorize")
+            .about("Bound outliers at fixed values or percentiles")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("min").long("min").value_parser(value_parser!(f64)).allow_negative_numbers(true))
+            .arg(Arg::new("max").long("max").value_parser(value_parser!(f64)).allow_negative_numbers(true))
+            .arg(Arg::new("lower-pct").long("lower-pct").value_parser(value_parser!(f64))
+                .conflicts_with("min").help("Clip below this percentile (0-100), e.g. 1"))
+            .arg(Arg::new("upper-pct").long("upper-pct").value_parser(value_parser!(f64))
+                .conflicts_with("max").help("Clip above this percentile (0-100), e.g. 99"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("cum")
+            .about("Running totals and counters, optionally per partition")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("over").long("over").help("Partition columns"))
+            .arg(Arg::new("order-by").long("order-by").help("Sort rows before accumulating"))
+            .arg(Arg::new("ops").long("ops").required(true)
+                .help("cumsum/cummin/cummax/cumprod:column, or cumcount, e.g. cumsum:amount,cumcount"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("rank")
+            .about("Add a rank column, optionally within groups")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("by").long("by").required(true))
+            .arg(Arg::new("method").long("method").default_value("dense")
+                .value_parser(["dense", "min", "max", "average", "ordinal"]))
+            .arg(Arg::new("descending").long("descending").action(ArgAction::SetTrue)
+                .help("Rank the largest value first"))
+            .arg(Arg::new("per").long("per").help("Rank within each group"))
+            .arg(Arg::new("into").long("into").help("Output column (default: <by>_rank)"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("pct-change")
+            .about("Period-over-period percentage change")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("order-by").long("order-by").help("Sort rows before comparing periods"))
+            .arg(Arg::new("per").long("per").help("Compare periods within each group"))
+            .arg(Arg::new("periods").long("periods").default_value("1").value_parser(value_parser!(i64))
+                .help("How many rows back to compare against"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("interpolate")
+            .about("Fill gaps in numeric series by interpolation")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("method").long("method").default_value("linear")
+                .value_parser(["linear", "nearest"]))
+            .arg(Arg::new("over").long("over").help("Order rows by this column (e.g. time) first"))
+            .arg(Arg::new("per").long("per").help("Interpolate each group separately, e.g. per sensor"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("round")
+            .about("Round numeric columns to a fixed number of decimals")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true)
+                .help("column=decimals pairs, e.g. amount=2,rate=4"))
+            .arg(Arg::new("mode").long("mode").default_value("round")
+                .value_parser(["round", "floor", "ceil"]))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("lag")
+            .about("Add lagged, lead and differenced columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("periods").long("periods").default_value("1").help("Offsets in rows, e.g. 1,7"))
+            .arg(Arg::new("kinds").long("kinds").default_value("lag")
+                .help("Any of lag, lead, diff, e.g. lag,diff"))
+            .arg(Arg::new("over").long("over").help("Partition columns"))
+            .arg(Arg::new("order-by").long("order-by").help("Sort rows before shifting"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("tz")
+            .about("Convert datetime columns between time zones")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("from").long("from").help("Zone of naive timestamps, e.g. UTC"))
+            .arg(Arg::new("to").long("to").required(true).help("Target zone, e.g. America/New_York"))
+            .arg(Arg::new("ambiguous").long("ambiguous").default_value("raise")
+                .value_parser(["raise", "earliest", "latest", "null"])
+                .help("How to resolve naive times repeated by a DST change"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("clean-text")
+            .about("Normalize string columns before joins and dedup")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("columns").long("columns").required(true))
+            .arg(Arg::new("ops").long("ops").default_value("trim,collapse-spaces")
+                .help("Applied in order: trim, lower, upper, collapse-spaces, strip-punct, strip-accents"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("replace-re")
+            .about("Regex find-and-replace in string columns")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("pattern").long("pattern").required(true))
+            .arg(Arg::new("with").long("with").default_value("")
+                .help("Replacement; $1, $2 refer to capture groups"))
+            .arg(Arg::new("first").long("first").action(ArgAction::SetTrue)
+                .help("Replace only the first match in each value"))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("tokens")
+            .about("Token and n-gram frequencies of a text column")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("column").long("column").required(true))
+            .arg(Arg::new("ngram").long("ngram").default_value("1").help("N-gram sizes, e.g. 1,2"))
+            .arg(Arg::new("top").long("top").value_parser(value_parser!(usize))
+                .help("Only keep the most frequent tokens"))
+            .arg(Arg::new("output").short('o').long("output")
+                .help("Write the result here instead of printing it")))
+        .subcommand(Command::new("compact")
+            .about("Merge small Parquet part files into larger ones")
+            .arg(Arg::new("input").required(true).help("Dataset directory"))
+            .arg(Arg::new("target-size").long("target-size").default_value("256MB")
+                .value_parser(parse_size)
+                .help("Approximate size of each merged file"))
+            .arg(Arg::new("output").short('o').long("output").required(true).help("Output directory")))
+        .subcommand(Command::new("repartition")
+            .about("Re-cluster a dataset by key, sorted within each partition")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("by").long("by").required(true))
+            .arg(Arg::new("sort-within").long("sort-within").help("Sort rows within each partition"))
+            .arg(Arg::new("partitions").long("partitions").value_parser(value_parser!(usize))
+                .help("Write up to this many files of contiguous key ranges, never splitting a key, instead of one key=value directory per key"))
+            .arg(Arg::new("output").short('o').long("output").required(true).help("Output directory")))
+        .subcommand(Command::new("convert-dir")
+            .about("Convert every matching file in a directory tree, in parallel")
+            .arg(Arg::new("input").required(true).help("Input directory"))
+            .arg(Arg::new("output").required(true).help("Output directory, mirroring the input tree"))
+            .arg(Arg::new("from").long("from").required(true)
+                .value_parser(["csv", "parquet", "pq", "json", "jsonl", "xml"]))
+            .arg(Arg::new("to").long("to").required(true)
+                .value_parser(["csv", "parquet", "pq", "jsonl", "ndjson", "xlsx"]))
+            .arg(Arg::new("jobs").short('j').long("jobs").value_parser(value_parser!(usize))
+                .help("Parallel conversions (default: one per core)")))
+        .subcommand(Command::new("validate")
+            .about("Check a file against a schema and data-quality rules")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("schema").long("schema")
+                .help("JSON object mapping column names to expected dtypes"))
+            .arg(Arg::new("not-null").long("not-null").help("Columns that must not contain nulls"))
+            .arg(Arg::new("unique").long("unique").help("Columns whose values must be unique")))
+        .subcommand(Command::new("sample")
+            .about("Draw a random sample of rows")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("size").short('n').long("size").value_parser(value_parser!(usize))
+                .help("Number of rows to draw (all methods except systematic)"))
+            .arg(Arg::new("fraction").long("fraction").value_parser(value_parser!(f64))
+                .conflicts_with("size")
+                .help("Share of rows to draw instead of --size, e.g. 0.01"))
+            .arg(Arg::new("method").long("method").default_value("random")
+                .value_parser(["random", "reservoir", "stratified", "systematic", "weighted", "cluster", "bootstrap"])
+                .help("reservoir streams the input instead of loading it"))
+            .arg(Arg::new("stratify").long("stratify").help("Column to stratify on (--method stratified)"))
+            .arg(Arg::new("every").long("every").value_parser(value_parser!(usize))
+                .help("Take every Nth row (--method systematic)"))
+            .arg(Arg::new("offset").long("offset").value_parser(value_parser!(usize))
+                .help("Index of the first row taken (--method systematic, default 0)"))
+            .arg(Arg::new("weight-column").long("weight-column")
+                .help("Column of non-negative draw weights (--method weighted)"))
+            .arg(Arg::new("replace").long("replace").action(ArgAction::SetTrue)
+                .help("Sample with replacement (--method weighted)"))
+            .arg(Arg::new("cluster-column").long("cluster-column")
+                .help("Column whose groups are kept or dropped as a whole (--method cluster)"))
+            .arg(Arg::new("clusters").long("clusters").value_parser(value_parser!(usize))
+                .help("Number of groups to draw (--method cluster; or use --fraction)"))
+            .arg(Arg::new("resamples").long("resamples").value_parser(value_parser!(usize))
+                .help("Write this many bootstrap resamples as OUTPUT_0000, OUTPUT_0001, ... (--method bootstrap)"))
+            .arg(Arg::new("seed").long("seed").value_parser(value_parser!(u64)))
+            .arg(Arg::new("output").short('o').long("output").required(true)))
+        .subcommand(Command::new("split")
+            .about("Split rows into train and test sets")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("method").long("method").default_value("random")
+                .value_parser(["random", "time"])
+                .help("time puts the newest rows in test instead of drawing them at random"))
+            .arg(Arg::new("test-size").long("test-size").default_value("0.2").value_parser(value_parser!(f64)))
+            .arg(Arg::new("time-column").long("time-column").help("Column to order by (--method time)"))
+            .arg(Arg::new("cutoff").long("cutoff")
+                .help("Rows at or after this time go to test (--method time; overrides --test-size)"))
+            .arg(Arg::new("stratify").long("stratify").help("Keep this column's class balance in both sets"))
+            .arg(Arg::new("stratify-bins").long("stratify-bins").value_parser(value_parser!(usize))
+                .requires("stratify")
+                .help("Cut a continuous --stratify column into this many quantile bins first"))
+            .arg(Arg::new("group-column").long("group-column").conflicts_with("stratify")
+                .help("Keep all rows of each group on the same side; --test-size is a share of groups"))
+            .arg(Arg::new("seed").long("seed").value_parser(value_parser!(u64)))
+            .arg(Arg::new("train").long("train").required(true).help("Train output path"))
+            .arg(Arg::new("test").long("test").required(true).help("Test output path")))
+        .subcommand(Command::new("balance")
+            .about("Report class frequencies and optionally rebalance them")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("target").long("target").required(true).help("Class label column"))
+            .arg(Arg::new("method").long("method").value_parser(["undersample", "oversample"])
+                .help("Write a rebalanced copy to --output"))
+            .arg(Arg::new("ratio").long("ratio").default_value("1.0").value_parser(value_parser!(f64))
+                .help("Target smallest/largest class size ratio, 1.0 for equal classes"))
+            .arg(Arg::new("seed").long("seed").value_parser(value_parser!(u64)))
+            .arg(Arg::new("output").short('o').long("output")))
+        .subcommand(Command::new("leak-check")
+            .about("Report rows shared between train and test files")
+            .arg(Arg::new("train").required(true))
+            .arg(Arg::new("test").required(true))
+            .arg(Arg::new("key").long("key").help("Compare on these columns instead of whole rows"))
+            .arg(Arg::new("near-duplicate").long("near-duplicate").action(ArgAction::SetTrue)
+                .help("Also match rows differing only in case, whitespace or float rounding"))
+            .arg(Arg::new("max-overlap").long("max-overlap").default_value("0").value_parser(value_parser!(f64))
+                .help("Fail when more than this share of test rows overlaps (0 = any overlap fails)")))
+        .subcommand(Command::new("kfold")
+            .about("Split rows into K cross-validation folds")
+            .arg(Arg::new("input").required(true))
+            .arg(Arg::new("folds").long("folds").default_value("5").value_parser(value_parser!(usize)))
+            .arg(Arg::new("stratify").long("stratify").help("Keep this column's class balance in every fold"))
+            .arg(Arg::new("seed").long("seed").value_parser(value_parser!(u64)))
+            .arg(Arg::new("assignments").long("assignments").action(ArgAction::SetTrue)
+                .help("Write one file with a fold column instead of per-fold train/test files"))
+            .arg(Arg::new("output").short('o').long("output").required(true)
+                .help("Directory receiving fold_<i>/train and fold_<i>/test (a file with --assignments)")))
+        .subcommand(Command::new("schema-diff")
+            .about("Compare two schemas; exits non-zero on incompatible changes")
+            .arg(Arg::new("old").required(true))
+            .arg(Arg::new("new").required(true)))
 }
@@ -0,0 +1,69 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::sample::{random_seed, sample_bootstrap, sample_random};
+
+pub fn balance_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let target = m.get_one::<String>("target").unwrap();
+    let method = m.get_one::<String>("method");
+    let ratio = *m.get_one::<f64>("ratio").unwrap();
+    let output = m.get_one::<String>("output");
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    println!("Class counts ({target}):");
+    print_class_counts(&df, target)?;
+    // Without --method this is only a report
+    let Some(method) = method else { return Ok(()) };
+    let Some(output) = output else { bail!("--method {method} needs -o <output>"); };
+    if !(ratio > 0.0 && ratio <= 1.0) { bail!("--ratio must be in (0, 1], got {ratio}"); }
+    let seed = m.get_one::<u64>("seed").copied().unwrap_or_else(random_seed);
+
+    let out = rebalance(&df, target, method, ratio, seed)?;
+    write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+    println!("Rebalanced ({method}, seed: {seed}):");
+    print_class_counts(&out, target)?;
+    Ok(())
+}
+
+/// undersample caps every class at `minority / ratio` rows; oversample tops every class up to
+/// `majority * ratio` rows by redrawing its rows with replacement. A ratio of 1.0 equalizes.
+pub fn rebalance(df: &DataFrame, target: &str, method: &str, ratio: f64, seed: u64) -> Result<DataFrame> {
+    let classes = df.partition_by_stable([target], true)?;
+    let smallest = classes.iter().map(DataFrame::height).min().unwrap_or(0);
+    let largest = classes.iter().map(DataFrame::height).max().unwrap_or(0);
+    let mut out = df.clear();
+    for (i, class) in classes.iter().enumerate() {
+        let seed = Some(seed.wrapping_add(i as u64));
+        let part = match method {
+            "undersample" => sample_random(class, (smallest as f64 / ratio).round() as usize, seed)?,
+            "oversample" => {
+                let want = (largest as f64 * ratio).round() as usize;
+                let mut part = class.clone();
+                if want > class.height() {
+                    part.vstack_mut(&sample_bootstrap(class, want - class.height(), seed)?)?;
+                }
+                part
+            }
+            other => bail!("Unsupported method={other}. Use undersample or oversample."),
+        };
+        out.vstack_mut(&part)?;
+    }
+    Ok(out)
+}
+
+fn print_class_counts(df: &DataFrame, target: &str) -> Result<()> {
+    let counts = df.clone().lazy()
+        .group_by([col(target)])
+        .agg([len().alias("count")])
+        .sort(["count", target], SortMultipleOptions::default().with_order_descending_multi([true, false]))
+        .collect()?;
+    let total = df.height().max(1) as f64;
+    let values = counts.column(target)?.cast(&DataType::String)?;
+    for (value, n) in values.str()?.into_iter().zip(counts.column("count")?.idx()?.into_iter()) {
+        let n = n.unwrap_or(0);
+        println!("  {}: {} ({:.1}%)", value.unwrap_or("null"), n, 100.0 * n as f64 / total);
+    }
+    Ok(())
+}
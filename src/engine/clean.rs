@@ -0,0 +1,162 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::{parse_names, parse_pairs};
+
+pub fn dedup_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let subset = m.get_one::<String>("subset");
+    let keep = m.get_one::<String>("keep").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let keep = match keep.as_str() {
+        "first" => UniqueKeepStrategy::First,
+        "last" => UniqueKeepStrategy::Last,
+        "none" => UniqueKeepStrategy::None,
+        other => bail!("Unsupported keep={}. Use first, last or none.", other),
+    };
+    let subset = subset.map(|s| parse_names(s).into_iter().map(Into::into).collect());
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.unique_stable(subset, keep).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn fillna_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let strategy = m.get_one::<String>("strategy").unwrap();
+    let columns = m.get_one::<String>("columns");
+    let value = m.get_one::<String>("value");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.clone().collect_schema()?;
+    // Statistical strategies default to numeric columns only.
+    let numeric_only = matches!(strategy.as_str(), "mean" | "median");
+    let names: Vec<String> = match columns {
+        Some(c) => parse_names(c),
+        None => schema.iter()
+            .filter(|(_, dt)| !numeric_only || dt.is_numeric())
+            .map(|(n, _)| n.to_string())
+            .collect(),
+    };
+
+    let mut exprs = vec![];
+    for name in &names {
+        let c = col(name.as_str());
+        exprs.push(match strategy.as_str() {
+            "mean" => c.clone().fill_null(c.mean()),
+            "median" => c.clone().fill_null(c.median()),
+            "mode" => c.clone().fill_null(c.mode().first()),
+            "constant" => {
+                let Some(v) = value else { bail!("--strategy constant requires --value"); };
+                c.fill_null(parse_literal(v))
+            }
+            "forward" => c.fill_null_with_strategy(FillNullStrategy::Forward(None)),
+            "backward" => c.fill_null_with_strategy(FillNullStrategy::Backward(None)),
+            other => bail!("Unsupported strategy={}.", other),
+        });
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn dropna_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let subset = m.get_one::<String>("subset");
+    let how = m.get_one::<String>("how").unwrap();
+    let thresh = m.get_one::<usize>("thresh").copied();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let names: Vec<String> = match subset {
+        Some(s) => parse_names(s),
+        None => lf.clone().collect_schema()?.iter_names().map(|n| n.to_string()).collect(),
+    };
+    if names.is_empty() { bail!("No columns to check for nulls."); }
+
+    // Count non-null values per row across the subset and compare against
+    // the required minimum: all of them (any), one (all), or --thresh.
+    let non_null = names.iter()
+        .map(|c| col(c.as_str()).is_not_null().cast(DataType::UInt32))
+        .reduce(|a, b| a + b)
+        .unwrap();
+    let required = match (thresh, how.as_str()) {
+        (Some(n), _) => n,
+        (None, "any") => names.len(),
+        (None, "all") => 1,
+        (None, other) => bail!("Unsupported how={}. Use any or all.", other),
+    };
+    let df = lf.filter(non_null.gt_eq(lit(required as u32))).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn interpolate_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let over = m.get_one::<String>("over");
+    let per = m.get_one::<String>("per").map(|s| parse_names(s)).unwrap_or_default();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let method = match m.get_one::<String>("method").unwrap().as_str() {
+        "linear" => InterpolationMethod::Linear,
+        "nearest" => InterpolationMethod::Nearest,
+        other => bail!("Unsupported method={}. Use linear or nearest.", other),
+    };
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if let Some(order) = over {
+        lf = lf.sort(parse_names(order), SortMultipleOptions::default());
+    }
+    // Only interior gaps are filled; leading and trailing nulls have nothing to interpolate from.
+    let partition: Vec<Expr> = per.iter().map(|c| col(c.as_str())).collect();
+    let exprs = columns.iter().map(|c| {
+        let e = col(c.as_str()).interpolate(method);
+        let e = if partition.is_empty() { e } else { e.over(partition.clone()) };
+        e.alias(c.as_str())
+    }).collect::<Vec<_>>();
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn replace_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let pairs = parse_pairs(m.get_one::<String>("map").unwrap())?;
+    let regex = m.get_flag("regex");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let exprs = columns.iter().map(|c| {
+        // Earlier mappings win, so nest from the last one outwards.
+        pairs.iter().rev().fold(col(c.as_str()), |acc, (from, to)| {
+            let hit = if regex {
+                col(c.as_str()).cast(DataType::String).str().contains(lit(from.as_str()), true)
+            } else {
+                col(c.as_str()).cast(DataType::String).eq(lit(from.as_str()))
+            };
+            let to = if to == "null" { lit(NULL).cast(DataType::String) } else { lit(to.as_str()) };
+            when(hit).then(to).otherwise(acc)
+        }).alias(c.as_str())
+    }).collect::<Vec<_>>();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+// Numbers and booleans become typed literals; anything else stays a string.
+pub(crate) fn parse_literal(v: &str) -> Expr {
+    if let Ok(i) = v.parse::<i64>() {
+        lit(i)
+    } else if let Ok(f) = v.parse::<f64>() {
+        lit(f)
+    } else if let Ok(b) = v.parse::<bool>() {
+        lit(b)
+    } else {
+        lit(v.to_string())
+    }
+}
@@ -0,0 +1,407 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::io::{parse_dtype, write_df_with, infer_reader, infer_reader_with, ReadOptions, WriteOptions};
+use super::{parse_names, parse_pairs};
+
+pub fn rename_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut pairs = vec![];
+    if let Some(path) = m.get_one::<String>("from-file") {
+        // first two columns of the mapping file are old and new names
+        let map = infer_reader(path)?.collect()?;
+        if map.width() < 2 { bail!("Mapping file {path} needs two columns: old,new"); }
+        let old = map.get_columns()[0].cast(&DataType::String)?;
+        let new = map.get_columns()[1].cast(&DataType::String)?;
+        for (o, n) in old.str()?.into_iter().zip(new.str()?.into_iter()) {
+            if let (Some(o), Some(n)) = (o, n) { pairs.push((o.to_string(), n.to_string())); }
+        }
+    }
+    if let Some(spec) = m.get_one::<String>("map") {
+        pairs.extend(parse_pairs(spec)?);
+    }
+    if pairs.is_empty() { bail!("No renames provided. Use --map or --from-file."); }
+
+    let mut df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    for (old, new) in pairs {
+        df.rename(&old, new.as_str().into())?;
+    }
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn cast_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let types = parse_pairs(m.get_one::<String>("types").unwrap())?;
+    let date_format = m.get_one::<String>("date-format");
+    let strict = m.get_one::<String>("mode").unwrap() == "strict";
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.clone().collect_schema()?;
+    let mut exprs = vec![];
+    for (name, ty) in types {
+        let dt = parse_dtype(&ty)?;
+        let Some(src) = schema.get(name.as_str()) else { bail!("Column not found: {name}"); };
+        let c = col(name.as_str());
+        // Strings with an explicit format go through strptime; everything else is a plain cast.
+        let expr = match (&dt, src, date_format) {
+            (DataType::Date, DataType::String, Some(fmt)) => c.str().to_date(strptime(fmt, strict)),
+            (DataType::Datetime(tu, _), DataType::String, Some(fmt)) => {
+                c.str().to_datetime(Some(*tu), None, strptime(fmt, strict), lit("raise"))
+            }
+            _ if strict => c.strict_cast(dt),
+            _ => c.cast(dt),
+        };
+        exprs.push(expr.alias(name.as_str()));
+    }
+    // lenient casts turn unparseable values into nulls; strict ones fail here
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn parse_dates_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let format = m.get_one::<String>("format");
+    let tz = m.get_one::<String>("tz");
+    let as_date = m.get_flag("date");
+    let strict = m.get_flag("strict");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let opts = StrptimeOptions { format: format.map(|f| f.as_str().into()), strict: false, ..Default::default() };
+    let parsed = |c: &str| {
+        let s = col(c).cast(DataType::String).str();
+        if as_date {
+            s.to_date(opts.clone())
+        } else {
+            // Naive values are localized to --tz; values carrying an offset (%z) are converted to it.
+            s.to_datetime(Some(TimeUnit::Microseconds), tz.map(|t| t.as_str().into()), opts.clone(), lit("raise"))
+        }
+    };
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    // Report values that were present but failed to parse before they become nulls.
+    let mut failed = 0;
+    for c in &columns {
+        let bad = df.clone().lazy()
+            .filter(col(c.as_str()).is_not_null().and(parsed(c).is_null()))
+            .select([col(c.as_str()).cast(DataType::String)])
+            .collect()?;
+        if bad.height() > 0 {
+            let examples: Vec<&str> = bad.get_columns()[0].str()?.into_no_null_iter().take(3).collect();
+            eprintln!("warning: {c}: {} unparseable value(s), e.g. {examples:?}", bad.height());
+            failed += bad.height();
+        }
+    }
+    if strict && failed > 0 { bail!("{failed} value(s) could not be parsed"); }
+
+    let exprs = columns.iter().map(|c| parsed(c).alias(c.as_str())).collect::<Vec<_>>();
+    let df = df.lazy().with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn tz_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let from = m.get_one::<String>("from");
+    let to = m.get_one::<String>("to").unwrap();
+    let ambiguous = m.get_one::<String>("ambiguous").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.clone().collect_schema()?;
+    let mut exprs = vec![];
+    for c in &columns {
+        let x = col(c.as_str());
+        let e = match schema.get(c.as_str()) {
+            // Naive timestamps are first localized to --from; DST gaps fail, overlaps follow --ambiguous.
+            Some(DataType::Datetime(_, None)) => {
+                let Some(from) = from else { bail!("Column {c} has no time zone; pass --from to localize it"); };
+                x.dt().replace_time_zone(Some(from.as_str().into()), lit(ambiguous.as_str()), NonExistent::Raise)
+                    .dt().convert_time_zone(to.as_str().into())
+            }
+            Some(DataType::Datetime(_, Some(_))) => x.dt().convert_time_zone(to.as_str().into()),
+            Some(other) => bail!("Column {c} is {other:?}, not a datetime; run parse-dates first"),
+            None => bail!("Column not found: {c}"),
+        };
+        exprs.push(e.alias(c.as_str()));
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+fn strptime(fmt: &str, strict: bool) -> StrptimeOptions {
+    StrptimeOptions { format: Some(fmt.into()), strict, ..Default::default() }
+}
+
+pub fn bin_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let column = m.get_one::<String>("column").unwrap();
+    let labels = m.get_one::<String>("labels").map(|s| parse_names(s));
+    let left_closed = m.get_flag("left-closed");
+    let into = m.get_one::<String>("into").cloned().unwrap_or_else(|| format!("{column}_bin"));
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let mut breaks: Vec<f64> = match (m.get_one::<String>("breaks"), m.get_one::<usize>("quantiles")) {
+        (Some(b), None) => b.split(',').map(|v| v.trim().parse::<f64>()).collect::<Result<_, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid --breaks: {e}"))?,
+        (None, Some(&q)) if q >= 2 => {
+            // q equal-frequency bins need the q-1 inner quantiles as breaks.
+            let qs: Vec<Expr> = (1..q)
+                .map(|i| col(column.as_str()).cast(DataType::Float64)
+                    .quantile(lit(i as f64 / q as f64), QuantileInterpolOptions::Linear)
+                    .alias(&format!("q{i}")))
+                .collect();
+            let row = lf.clone().select(qs).collect()?;
+            row.get_columns().iter().filter_map(|s| s.f64().ok().and_then(|ca| ca.get(0))).collect()
+        }
+        (None, Some(_)) => bail!("--quantiles needs at least 2 bins"),
+        _ => bail!("Pass exactly one of --breaks or --quantiles"),
+    };
+    if let Some(b) = breaks.iter().find(|b| !b.is_finite()) {
+        bail!("Invalid --breaks: {b} is not a finite number");
+    }
+    breaks.sort_by(f64::total_cmp);
+    breaks.dedup();
+
+    // n breaks make n+1 bins, from (-inf, b0] up to (bn, inf).
+    let mut edges = vec![f64::NEG_INFINITY];
+    edges.extend(&breaks);
+    edges.push(f64::INFINITY);
+    let labels = match labels {
+        Some(l) if l.len() != edges.len() - 1 => {
+            let from = match m.get_one::<usize>("quantiles") {
+                Some(q) => format!("--quantiles {q}"),
+                None => format!("--breaks with {} edges", breaks.len()),
+            };
+            bail!("{} labels were given but {from} yields {} bins", l.len(), edges.len() - 1)
+        }
+        Some(l) => l,
+        None => edges.windows(2)
+            .map(|w| if left_closed { format!("[{}, {})", w[0], w[1]) } else { format!("({}, {}]", w[0], w[1]) })
+            .collect(),
+    };
+
+    let x = col(column.as_str()).cast(DataType::Float64);
+    let binned = edges.windows(2).zip(&labels).rev().fold(lit(NULL).cast(DataType::String), |acc, (w, label)| {
+        let upper = if left_closed { x.clone().lt(lit(w[1])) } else { x.clone().lt_eq(lit(w[1])) };
+        let lower = if left_closed { x.clone().gt_eq(lit(w[0])) } else { x.clone().gt(lit(w[0])) };
+        when(lower.and(upper)).then(lit(label.as_str())).otherwise(acc)
+    });
+    let df = lf.with_column(binned.alias(into.as_str())).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn encode_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let method = m.get_one::<String>("method").unwrap();
+    let drop_first = m.get_flag("drop-first");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let lf = match method.as_str() {
+        // Dense rank over sorted values gives stable 0-based codes.
+        "label" => lf.with_columns(columns.iter().map(|c| {
+            let opts = RankOptions { method: RankMethod::Dense, descending: false };
+            (col(c.as_str()).rank(opts, None) - lit(1)).alias(c.as_str())
+        }).collect::<Vec<_>>()),
+        "onehot" => {
+            let mut dummies = vec![];
+            for c in &columns {
+                let values = lf.clone()
+                    .select([col(c.as_str()).cast(DataType::String).drop_nulls().unique().sort(Default::default())])
+                    .collect()?;
+                let values: Vec<String> = values.get_columns()[0].str()?.into_no_null_iter().map(String::from).collect();
+                for v in values.iter().skip(usize::from(drop_first)) {
+                    dummies.push(col(c.as_str()).cast(DataType::String).eq(lit(v.as_str()))
+                        .fill_null(lit(false))
+                        .cast(DataType::UInt8)
+                        .alias(&format!("{c}_{v}")));
+                }
+            }
+            lf.with_columns(dummies).drop(columns.clone())
+        }
+        other => bail!("Unsupported method={}. Use onehot or label.", other),
+    };
+    let df = lf.collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+/// Fitted scaling parameters; every method reduces to `(x - center) / scale`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ScaleParams {
+    method: String,
+    columns: BTreeMap<String, ColumnScale>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ColumnScale {
+    center: f64,
+    scale: f64,
+}
+
+pub fn scale_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = m.get_one::<String>("columns").map(|s| parse_names(s));
+    let method = m.get_one::<String>("method").unwrap();
+    let params_in = m.get_one::<String>("params-in");
+    let params_out = m.get_one::<String>("params-out");
+    let output = m.get_one::<String>("output").unwrap();
+    if let Some(path) = params_out {
+        if !m.get_flag("force") && std::path::Path::new(path).exists() {
+            bail!("Output {path} already exists; pass --force to overwrite it");
+        }
+    }
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let params = match params_in {
+        // Reapply parameters fitted on another file (e.g. train -> test).
+        Some(path) => serde_json::from_str::<ScaleParams>(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow::anyhow!("Invalid scale parameters in {path}: {e}"))?,
+        None => {
+            let Some(columns) = columns else { bail!("--columns is required unless --params-in is given"); };
+            fit_scale(&lf, &columns, method)?
+        }
+    };
+
+    let exprs = params.columns.iter()
+        .map(|(c, p)| {
+            let scale = if p.scale == 0.0 { 1.0 } else { p.scale };
+            ((col(c.as_str()).cast(DataType::Float64) - lit(p.center)) / lit(scale)).alias(c.as_str())
+        })
+        .collect::<Vec<_>>();
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    if let Some(path) = params_out {
+        std::fs::write(path, serde_json::to_string_pretty(&params)?)?;
+    }
+    Ok(())
+}
+
+fn fit_scale(lf: &LazyFrame, columns: &[String], method: &str) -> Result<ScaleParams> {
+    let mut exprs = vec![];
+    for c in columns {
+        let x = col(c.as_str()).cast(DataType::Float64);
+        let q = |p: f64| x.clone().quantile(lit(p), QuantileInterpolOptions::Linear);
+        let (center, scale) = match method {
+            "zscore" => (x.clone().mean(), x.clone().std(1)),
+            "minmax" => (x.clone().min(), x.clone().max() - x.clone().min()),
+            "robust" => (x.clone().median(), q(0.75) - q(0.25)),
+            other => bail!("Unsupported method={}. Use zscore, minmax or robust.", other),
+        };
+        exprs.push(center.alias(&format!("{c}__center")));
+        exprs.push(scale.alias(&format!("{c}__scale")));
+    }
+    let row = lf.clone().select(exprs).collect()?;
+    let get = |name: String| -> Result<f64> {
+        Ok(row.column(&name)?.f64()?.get(0).unwrap_or(0.0))
+    };
+    let mut fitted = BTreeMap::new();
+    for c in columns {
+        fitted.insert(c.clone(), ColumnScale {
+            center: get(format!("{c}__center"))?,
+            scale: get(format!("{c}__scale"))?,
+        });
+    }
+    Ok(ScaleParams { method: method.to_string(), columns: fitted })
+}
+
+pub fn add_index_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let name = m.get_one::<String>("name").unwrap();
+    let start = *m.get_one::<IdxSize>("start").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if lf.clone().collect_schema()?.contains(name) {
+        bail!("Column {name} already exists; pick another --name");
+    }
+    let df = lf.with_row_index(name.as_str(), Some(start)).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn clip_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let min = m.get_one::<f64>("min").copied();
+    let max = m.get_one::<f64>("max").copied();
+    let lower_pct = m.get_one::<f64>("lower-pct").copied();
+    let upper_pct = m.get_one::<f64>("upper-pct").copied();
+    let output = m.get_one::<String>("output").unwrap();
+
+    if min.is_none() && max.is_none() && lower_pct.is_none() && upper_pct.is_none() {
+        bail!("Nothing to clip. Use --min/--max or --lower-pct/--upper-pct.");
+    }
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.clone().collect_schema()?;
+    let mut exprs = vec![];
+    for c in &columns {
+        let Some(dtype) = schema.get(c.as_str()) else { bail!("Column not found: {c}"); };
+        let x = col(c.as_str()).cast(DataType::Float64);
+        // Percentile bounds (winsorizing) are fitted on this column first.
+        let pct = |p: f64| -> Result<Option<f64>> {
+            let row = lf.clone()
+                .select([x.clone().quantile(lit(p / 100.0), QuantileInterpolOptions::Linear)])
+                .collect()?;
+            Ok(row.get_columns()[0].f64()?.get(0))
+        };
+        let lo = match lower_pct { Some(p) => pct(p)?, None => min };
+        let hi = match upper_pct { Some(p) => pct(p)?, None => max };
+
+        let mut clipped = x.clone();
+        if let Some(lo) = lo {
+            clipped = when(x.clone().lt(lit(lo))).then(lit(lo)).otherwise(clipped);
+        }
+        if let Some(hi) = hi {
+            clipped = when(x.clone().gt(lit(hi))).then(lit(hi)).otherwise(clipped);
+        }
+        // Integer columns stay integers when the bounds are whole numbers.
+        let whole = [lo, hi].iter().flatten().all(|b| b.fract() == 0.0);
+        if dtype.is_integer() && whole {
+            clipped = clipped.cast(dtype.clone());
+        }
+        exprs.push(clipped.alias(c.as_str()));
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn round_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let specs = parse_pairs(m.get_one::<String>("columns").unwrap())?;
+    let mode = m.get_one::<String>("mode").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut exprs = vec![];
+    for (c, digits) in specs {
+        let digits: u32 = digits.parse().map_err(|_| anyhow::anyhow!("Invalid decimals for {c}: {digits:?}"))?;
+        let x = col(c.as_str()).cast(DataType::Float64);
+        // floor/ceil work on the scaled value so they respect the decimals too.
+        let factor = lit(10f64.powi(digits as i32));
+        let e = match mode.as_str() {
+            "round" => x.round(digits),
+            "floor" => (x * factor.clone()).floor() / factor,
+            "ceil" => (x * factor.clone()).ceil() / factor,
+            other => bail!("Unsupported mode={}. Use round, floor or ceil.", other),
+        };
+        exprs.push(e.alias(c.as_str()));
+    }
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
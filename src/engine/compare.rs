@@ -0,0 +1,211 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use crate::io::{dtype_name, write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::parse_names;
+
+pub fn diff_cmd(m: &ArgMatches) -> Result<()> {
+    let old_path = m.get_one::<String>("old").unwrap();
+    let new_path = m.get_one::<String>("new").unwrap();
+    let keys = parse_names(m.get_one::<String>("key").unwrap());
+    let output = m.get_one::<String>("output");
+
+    let ropts = ReadOptions::from_matches(m);
+    let old = infer_reader_with(old_path, &ropts)?.collect()?;
+    let new = infer_reader_with(new_path, &ropts)?.collect()?;
+    let key_exprs: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+
+    let old_cols: Vec<String> = old.get_column_names().iter().map(|c| c.to_string()).collect();
+    let new_cols: Vec<String> = new.get_column_names().iter().map(|c| c.to_string()).collect();
+    let shared: Vec<&String> = old_cols.iter().filter(|c| new_cols.contains(c) && !keys.contains(c)).collect();
+
+    let anti = |a: &DataFrame, b: &DataFrame| {
+        a.clone().lazy()
+            .join(b.clone().lazy().select(key_exprs.clone()), key_exprs.clone(), key_exprs.clone(), JoinArgs::new(JoinType::Anti))
+            .collect()
+    };
+    let added = anti(&new, &old)?;
+    let removed = anti(&old, &new)?;
+
+    // Rows present on both sides, with one null-aware inequality flag per shared column.
+    let flags: Vec<Expr> = shared.iter()
+        .map(|c| col(c.as_str()).neq_missing(col(&format!("{c}__new"))).alias(&format!("__changed_{c}")))
+        .collect();
+    let any_changed = shared.iter()
+        .map(|c| col(&format!("__changed_{c}")))
+        .reduce(|a, b| a.or(b))
+        .unwrap_or(lit(false));
+    let matched = old.clone().lazy().join_builder()
+        .with(new.clone().lazy())
+        .left_on(key_exprs.clone())
+        .right_on(key_exprs.clone())
+        .how(JoinType::Inner)
+        .suffix("__new")
+        .finish()
+        .with_columns(flags)
+        .filter(any_changed)
+        .collect()?;
+
+    println!("Added rows:   {}", added.height());
+    println!("Removed rows: {}", removed.height());
+    println!("Changed rows: {}", matched.height());
+    for c in new_cols.iter().filter(|c| !old_cols.contains(c)) {
+        println!("+ column {c}");
+    }
+    for c in old_cols.iter().filter(|c| !new_cols.contains(c)) {
+        println!("- column {c}");
+    }
+    for c in &shared {
+        let n = matched.column(&format!("__changed_{c}"))?.bool()?.sum().unwrap_or(0);
+        if n > 0 { println!("~ column {c}: {n} changed"); }
+    }
+
+    if let Some(out) = output {
+        // One frame of differing rows tagged with _diff; changed rows carry the new values.
+        let mut changed_cols: Vec<Expr> = key_exprs.clone();
+        changed_cols.extend(shared.iter().map(|c| col(&format!("{c}__new")).alias(c.as_str())));
+        let parts = vec![
+            added.lazy().with_column(lit("added").alias("_diff")),
+            removed.lazy().with_column(lit("removed").alias("_diff")),
+            matched.lazy().select(changed_cols).with_column(lit("changed").alias("_diff")),
+        ];
+        let df = concat_lf_diagonal(parts, UnionArgs::default())?.collect()?;
+        write_df_with(&df, out, &WriteOptions::from_matches(m))?;
+    }
+    Ok(())
+}
+
+pub fn schema_diff_cmd(m: &ArgMatches) -> Result<()> {
+    let old_path = m.get_one::<String>("old").unwrap();
+    let new_path = m.get_one::<String>("new").unwrap();
+
+    let ropts = ReadOptions::from_matches(m);
+    let old = infer_reader_with(old_path, &ropts)?.collect_schema()?;
+    let new = infer_reader_with(new_path, &ropts)?.collect_schema()?;
+
+    let mut removed: Vec<(usize, &str, &DataType)> = old.iter().enumerate()
+        .filter(|(_, (name, _))| !new.contains(name))
+        .map(|(i, (name, dt))| (i, name.as_str(), dt))
+        .collect();
+    let mut added: Vec<(usize, &str, &DataType)> = new.iter().enumerate()
+        .filter(|(_, (name, _))| !old.contains(name))
+        .map(|(i, (name, dt))| (i, name.as_str(), dt))
+        .collect();
+
+    // A column that disappears and reappears at the same position with the
+    // same dtype is reported as a rename.
+    let mut renamed = vec![];
+    removed.retain(|&(i, name, dt)| {
+        match added.iter().position(|&(j, _, new_dt)| i == j && dt == new_dt) {
+            Some(k) => { renamed.push((name, added.remove(k).1)); false }
+            None => true,
+        }
+    });
+
+    let mut breaking = removed.len() + renamed.len();
+    for (_, name, dt) in &added {
+        println!("+ column {name}: {}", dtype_name(dt));
+    }
+    for (_, name, dt) in &removed {
+        println!("- column {name}: {}", dtype_name(dt));
+    }
+    for (from, to) in &renamed {
+        println!("~ column {from} renamed to {to}");
+    }
+    for (name, old_dt) in old.iter() {
+        let Some(new_dt) = new.get(name) else { continue };
+        if old_dt == new_dt { continue; }
+        let widening = is_widening(old_dt, new_dt);
+        if !widening { breaking += 1; }
+        println!("~ column {name}: {} -> {}{}", dtype_name(old_dt), dtype_name(new_dt),
+            if widening { " (widening)" } else { "" });
+    }
+
+    if breaking > 0 { bail!("{breaking} incompatible schema change(s)"); }
+    println!("Schemas are compatible");
+    Ok(())
+}
+
+// Changes existing values survive: larger ints, or ints/floats into a wider float
+// (64-bit ints into Float64 count too, as happens when a column gains decimals).
+fn is_widening(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    let rank = |dt: &DataType| match dt {
+        Int8 => Some((0, 1)), Int16 => Some((0, 2)), Int32 => Some((0, 3)), Int64 => Some((0, 4)),
+        UInt8 => Some((1, 1)), UInt16 => Some((1, 2)), UInt32 => Some((1, 3)), UInt64 => Some((1, 4)),
+        _ => None,
+    };
+    match (from, to) {
+        (Float32, Float64) => true,
+        (Int8 | Int16 | UInt8 | UInt16, Float32) => true,
+        (Int8 | Int16 | Int32 | Int64 | UInt8 | UInt16 | UInt32 | UInt64, Float64) => true,
+        _ => match (rank(from), rank(to)) {
+            (Some((a, x)), Some((b, y))) => (a == b && y > x) || (a == 1 && b == 0 && y > x),
+            _ => false,
+        },
+    }
+}
+
+pub fn leak_check_cmd(m: &ArgMatches) -> Result<()> {
+    let train_path = m.get_one::<String>("train").unwrap();
+    let test_path = m.get_one::<String>("test").unwrap();
+    let max_overlap = *m.get_one::<f64>("max-overlap").unwrap();
+
+    let ropts = ReadOptions::from_matches(m);
+    let mut train = infer_reader_with(train_path, &ropts)?;
+    let mut test = infer_reader_with(test_path, &ropts)?;
+    let train_schema = train.collect_schema()?;
+    let test_schema = test.collect_schema()?;
+    // Without --key a leak is a whole row (every shared column) appearing on both sides
+    let keys: Vec<String> = match m.get_one::<String>("key") {
+        Some(k) => parse_names(k),
+        None => test_schema.iter_names().filter(|c| train_schema.contains(c.as_str())).map(|c| c.to_string()).collect(),
+    };
+    if keys.is_empty() { bail!("Train and test share no columns to compare"); }
+    let test_rows = test.clone().select([len()]).collect()?.column("len")?.idx()?.get(0).unwrap_or(0) as usize;
+    let label = m.get_one::<String>("key").map(|k| format!("key {k}")).unwrap_or_else(|| "all shared columns".into());
+
+    let mut worst = 0.0f64;
+    let mut report = |kind: &str, train: LazyFrame, test: LazyFrame| -> Result<()> {
+        let n = overlapping_rows(train, test, &keys)?;
+        let share = if test_rows == 0 { 0.0 } else { n as f64 / test_rows as f64 };
+        println!("{kind} overlap on {label}: {n} of {test_rows} test rows ({:.2}%)", share * 100.0);
+        worst = worst.max(share);
+        Ok(())
+    };
+    report("Exact", train.clone(), test.clone())?;
+    if m.get_flag("near-duplicate") {
+        let norm = |lf: LazyFrame, schema: &Schema| lf.with_columns(
+            keys.iter().filter_map(|k| schema.get(k).map(|dt| normalized(k, dt))).collect::<Vec<_>>()
+        );
+        report("Near-duplicate", norm(train, &train_schema), norm(test, &test_schema))?;
+    }
+    if worst > max_overlap {
+        bail!("Leakage: {:.2}% of test rows also appear in train (allowed: {:.2}%)", worst * 100.0, max_overlap * 100.0);
+    }
+    Ok(())
+}
+
+/// Test rows whose `keys` also occur in train. Nulls match nulls, so a duplicated
+/// row with a missing value still counts as a leak.
+fn overlapping_rows(train: LazyFrame, test: LazyFrame, keys: &[String]) -> Result<usize> {
+    let on: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    let train_keys = train.select(on.clone()).unique(None, UniqueKeepStrategy::Any);
+    let shared = test
+        .join(train_keys, on.clone(), on, JoinArgs { join_nulls: true, ..JoinArgs::new(JoinType::Semi) })
+        .select([len()])
+        .collect()?;
+    Ok(shared.column("len")?.idx()?.get(0).unwrap_or(0) as usize)
+}
+
+// Near duplicates ignore case and spacing in text and tiny float differences
+fn normalized(name: &str, dtype: &DataType) -> Expr {
+    let c = col(name);
+    match dtype {
+        DataType::String => c.str().to_lowercase()
+            .str().replace_all(lit(r"\s+"), lit(" "), false)
+            .str().strip_chars(lit(NULL)),
+        DataType::Float32 | DataType::Float64 => c.round(6),
+        _ => c,
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::parse_names;
+
+pub fn join_cmd(m: &ArgMatches) -> Result<()> {
+    let left = m.get_one::<String>("left").unwrap();
+    let right = m.get_one::<String>("right").unwrap();
+    let how = m.get_one::<String>("how").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+    // right-hand columns whose names clash with the left get this suffix
+    let suffix = m.get_one::<String>("suffix").unwrap();
+
+    let ropts = ReadOptions::from_matches(m);
+    let mut l = infer_reader_with(left, &ropts)?;
+    let mut r = infer_reader_with(right, &ropts)?;
+    let join_type = match how.as_str() {
+        "inner" => JoinType::Inner,
+        "left" => JoinType::Left,
+        "full" | "outer" => JoinType::Full,
+        // semi keeps left rows with a match, anti keeps the unmatched ones; neither adds right columns
+        "semi" => JoinType::Semi,
+        "anti" => JoinType::Anti,
+        "asof" => JoinType::AsOf(asof_options(m)?),
+        "fuzzy" => {
+            let df = fuzzy_join(m, l, r)?;
+            write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+            return Ok(());
+        }
+        "cross" => {
+            let df = l.cross_join(r, Some(suffix.as_str().into())).collect()?;
+            write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+            return Ok(());
+        }
+        other => bail!("Unsupported join how={}. Use inner, left, full, cross, semi, anti, asof or fuzzy.", other),
+    };
+    let (left_on, right_on) = join_keys(m)?;
+    let left_on: Vec<Expr> = left_on.iter().map(|c| col(c.as_str())).collect();
+    let right_on: Vec<Expr> = right_on.iter().map(|c| col(c.as_str())).collect();
+    if left_on.is_empty() { bail!("--on (or --left-on/--right-on) is required for how={how}"); }
+    // Materialize the small lookup side once so the large side can stream through the hash table
+    let broadcast = m.get_one::<String>("broadcast");
+    match broadcast.map(String::as_str) {
+        Some("left") => l = l.collect()?.lazy(),
+        Some("right") => r = r.collect()?.lazy(),
+        _ => {}
+    }
+    if let Some(v) = m.get_one::<String>("validate") {
+        // "1" on a side means each key may appear there at most once
+        let (left_one, right_one) = match v.as_str() {
+            "1:1" => (true, true),
+            "1:m" => (true, false),
+            "m:1" => (false, true),
+            _ => (false, false),
+        };
+        if left_one { check_unique_keys(&l, &left_on, "left", v)?; }
+        if right_one { check_unique_keys(&r, &right_on, "right", v)?; }
+    }
+    if matches!(join_type, JoinType::AsOf(_)) {
+        if left_on.len() != 1 { bail!("asof joins match on exactly one column; use --by for exact-match keys"); }
+        // the as-of search needs both sides ordered by the key
+        l = l.sort_by_exprs(left_on.clone(), SortMultipleOptions::default());
+        r = r.sort_by_exprs(right_on.clone(), SortMultipleOptions::default());
+    }
+    let mut builder = l.join_builder()
+        .with(r)
+        .left_on(left_on)
+        .right_on(right_on)
+        .how(join_type)
+        .suffix(suffix.as_str());
+    // full joins otherwise keep both key columns, with the right one suffixed
+    if m.get_flag("coalesce") {
+        builder = builder.coalesce(true);
+    }
+    let df = builder.finish().with_streaming(broadcast.is_some()).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+// --on a,b and --on a --on b both mean a composite key; --left-on/--right-on pair up by position
+fn join_keys(m: &ArgMatches) -> Result<(Vec<String>, Vec<String>)> {
+    let names = |id: &str| -> Vec<String> {
+        m.get_many::<String>(id).into_iter().flatten()
+            .flat_map(|s| parse_names(s))
+            .collect()
+    };
+    let on = names("on");
+    if !on.is_empty() {
+        return Ok((on.clone(), on));
+    }
+    let (left_on, right_on) = (names("left-on"), names("right-on"));
+    if left_on.len() != right_on.len() {
+        bail!("--left-on has {} column(s) but --right-on has {}", left_on.len(), right_on.len());
+    }
+    Ok((left_on, right_on))
+}
+
+fn check_unique_keys(lf: &LazyFrame, keys: &[Expr], side: &str, validate: &str) -> Result<()> {
+    let dups = lf.clone()
+        .group_by(keys.to_vec())
+        .agg([len().alias("__rows")])
+        .filter(col("__rows").gt(lit(1)))
+        .collect()?;
+    if dups.height() == 0 { return Ok(()); }
+    let example = dups.get(0).unwrap_or_default();
+    let (key, rows) = example.split_at(keys.len());
+    let key = key.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+    bail!(
+        "--validate {validate} failed: {} key(s) repeat in the {side} file (e.g. [{key}] appears {} times)",
+        dups.height(), rows.first().map(|v| v.to_string()).unwrap_or_default()
+    )
+}
+
+fn asof_options(m: &ArgMatches) -> Result<AsOfOptions> {
+    let strategy = match m.get_one::<String>("strategy").map(String::as_str).unwrap_or("backward") {
+        "backward" => AsofStrategy::Backward,
+        "forward" => AsofStrategy::Forward,
+        "nearest" => AsofStrategy::Nearest,
+        other => bail!("Unsupported asof strategy: {other}. Use backward, forward or nearest."),
+    };
+    let by: Option<Vec<PlSmallStr>> = m.get_many::<String>("by")
+        .map(|v| v.flat_map(|s| parse_names(s)).map(PlSmallStr::from).collect());
+    let mut opts = AsOfOptions { strategy, left_by: by.clone(), right_by: by, ..Default::default() };
+    // 5 or 0.5 for numeric keys, a duration such as 5m or 2h30m for temporal ones
+    if let Some(t) = m.get_one::<String>("tolerance") {
+        if let Ok(i) = t.parse::<i64>() {
+            opts.tolerance = Some(AnyValue::Int64(i));
+        } else if let Ok(f) = t.parse::<f64>() {
+            opts.tolerance = Some(AnyValue::Float64(f));
+        } else {
+            opts.tolerance_str = Some(t.as_str().into());
+        }
+    }
+    Ok(opts)
+}
+
+// Each left row picks its most similar right key (case and surrounding whitespace ignored);
+// the chosen key lands in `<key>_match` with its similarity in `match_score`, and left rows
+// without a candidate above --similarity keep nulls.
+fn fuzzy_join(m: &ArgMatches, l: LazyFrame, r: LazyFrame) -> Result<DataFrame> {
+    let (left_on, right_on) = join_keys(m)?;
+    let ([lk], [rk]) = (left_on.as_slice(), right_on.as_slice()) else {
+        bail!("fuzzy joins match on exactly one string column");
+    };
+    let threshold = *m.get_one::<f64>("similarity").unwrap();
+    let score: fn(&str, &str) -> f64 = match m.get_one::<String>("algorithm").unwrap().as_str() {
+        "jarowinkler" => strsim::jaro_winkler,
+        "levenshtein" => strsim::normalized_levenshtein,
+        other => bail!("Unsupported fuzzy algorithm: {other}. Use jarowinkler or levenshtein."),
+    };
+
+    let mut left_df = l.collect()?;
+    let right_df = r.with_column(col(rk.as_str()).cast(DataType::String)).collect()?;
+    let mut candidates: Vec<&str> = right_df.column(rk)?.str()?.into_iter().flatten().collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    let candidates: Vec<(String, &str)> = candidates.into_iter().map(|c| (normalize(c), c)).collect();
+
+    let left_keys = left_df.column(lk)?.cast(&DataType::String)?;
+    let left_keys = left_keys.str()?;
+    let mut distinct: Vec<&str> = left_keys.into_iter().flatten().collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let best: HashMap<&str, (&str, f64)> = distinct.par_iter()
+        .filter_map(|k| best_match(k, &candidates, score, threshold).map(|b| (*k, b)))
+        .collect();
+    let (matched, scores): (Vec<Option<&str>>, Vec<Option<f64>>) = left_keys.into_iter()
+        .map(|k| match k.and_then(|k| best.get(k)) {
+            Some((c, s)) => (Some(*c), Some(*s)),
+            None => (None, None),
+        })
+        .unzip();
+
+    let match_col = format!("{rk}_match");
+    left_df.with_column(Series::new(match_col.as_str().into(), matched))?;
+    left_df.with_column(Series::new("match_score".into(), scores))?;
+    let df = left_df.lazy()
+        .join(right_df.lazy(), [col(match_col.as_str())], [col(rk.as_str())],
+            JoinArgs::new(JoinType::Left).with_suffix(Some(m.get_one::<String>("suffix").unwrap().as_str().into())))
+        .collect()?;
+    Ok(df)
+}
+
+fn normalize(s: &str) -> String {
+    s.trim().to_lowercase()
+}
+
+// Highest-scoring candidate at or above the threshold; ties go to the first in sorted order.
+fn best_match<'a>(key: &str, candidates: &[(String, &'a str)], score: fn(&str, &str) -> f64, threshold: f64) -> Option<(&'a str, f64)> {
+    let key = normalize(key);
+    let mut best: Option<(&'a str, f64)> = None;
+    for (norm, orig) in candidates {
+        let s = score(&key, norm);
+        if s >= threshold && !matches!(best, Some((_, b)) if b >= s) {
+            best = Some((*orig, s));
+        }
+    }
+    best
+}
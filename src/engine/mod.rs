@@ -2,7 +2,37 @@ use anyhow::{Result, bail};
 use clap::ArgMatches;
 use polars::prelude::*;
 use polars::sql::sql_expr;
-use crate::io::{write_df, infer_reader};
+use crate::io::{write_df_with, infer_reader, infer_reader_with, ReadOptions, WriteOptions};
+
+mod balance;
+mod clean;
+mod columns;
+mod compare;
+mod join;
+mod profile;
+mod reshape;
+mod sample;
+mod split;
+mod sql;
+mod stats;
+mod text;
+mod validate;
+mod window;
+
+pub use balance::balance_cmd;
+pub use clean::{dedup_cmd, dropna_cmd, fillna_cmd, interpolate_cmd, replace_cmd};
+pub use columns::{add_index_cmd, bin_cmd, cast_cmd, clip_cmd, encode_cmd, parse_dates_cmd, rename_cmd, round_cmd, scale_cmd, tz_cmd};
+pub use compare::{diff_cmd, leak_check_cmd, schema_diff_cmd};
+pub use join::join_cmd;
+pub use profile::{profile_cmd, profile_stats};
+pub use reshape::{concat_cmd, crosstab_cmd, explode_cmd, melt_cmd, pivot_cmd, transpose_cmd, unnest_cmd};
+pub use sample::{sample_cmd, sample_to_path, shuffle_cmd};
+pub use split::{kfold_cmd, kfold_to_paths, split_cmd, split_to_paths};
+pub use sql::{derive_cmd, sql_cmd};
+pub use stats::{corr_cmd, count_cmd, describe_cmd, top_cmd, value_counts_cmd};
+pub use text::{clean_text_cmd, concat_col_cmd, extract_cmd, mask_cmd, replace_re_cmd, split_col_cmd, tokens_cmd};
+pub use validate::{validate_cmd, validate_path};
+pub use window::{cum_cmd, lag_cmd, pct_change_cmd, rank_cmd, resample_cmd, window_cmd};
 
 fn parse_cols_opt(s: Option<&String>) -> Option<Vec<Expr>> {
     s.map(|csv| {
@@ -14,109 +44,287 @@ fn parse_cols_vec(s: &String) -> Vec<Expr> {
     s.split(',').map(|c| col(c.trim())).collect::<Vec<_>>()
 }
 
+fn parse_names(s: &str) -> Vec<String> {
+    s.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect()
+}
+
+// Parses `a=b,c=d` pairs as used by --map style options.
+fn parse_pairs(s: &str) -> Result<Vec<(String, String)>> {
+    s.split(',')
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| match p.split_once('=') {
+            Some((k, v)) => Ok((k.trim().to_string(), v.trim().to_string())),
+            None => bail!("Invalid mapping {p:?}; expected key=value"),
+        })
+        .collect()
+}
+
+// Parses `agg:col,agg:col` specs such as `mean:amount,sum:amount`.
+fn parse_agg_specs(s: &str) -> Result<Vec<(String, String)>> {
+    s.split(',')
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| match p.split_once(':') {
+            Some((agg, c)) => Ok((agg.trim().to_string(), c.trim().to_string())),
+            None => bail!("Invalid aggregation {p:?}; expected agg:column"),
+        })
+        .collect()
+}
+
+fn agg_expr(e: Expr, agg: &str) -> Result<Expr> {
+    Ok(match agg {
+        "sum" => e.sum(),
+        "mean" => e.mean(),
+        "min" => e.min(),
+        "max" => e.max(),
+        "median" => e.median(),
+        "std" => e.std(1),
+        "count" => e.count(),
+        "first" => e.first(),
+        "last" => e.last(),
+        "n_unique" => e.n_unique(),
+        other => bail!("Unsupported aggregation: {other}"),
+    })
+}
+
 // ----- Public command handlers -----
 pub fn filter_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
-    let where_expr = m.get_one::<String>("where").unwrap();
     let select = m.get_one::<String>("select");
     let output = m.get_one::<String>("output").unwrap();
 
-    let lf = plan_filter(input, where_expr, select)?;
+    let where_expr = combine_where(m);
+    let mut lf = plan_filter(input, &where_expr, select, &ReadOptions::from_matches(m))?;
+    // Slicing the lazy plan lets Polars stop reading once --limit matches are found
+    let offset = m.get_one::<usize>("offset").copied().unwrap_or(0);
+    let limit = m.get_one::<usize>("limit").copied();
+    if offset > 0 || limit.is_some() {
+        lf = lf.slice(offset as i64, limit.map_or(IdxSize::MAX, |n| n as IdxSize));
+    }
     let df = lf.collect()?;
-    write_df(&df, output)?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
     Ok(())
 }
 
+// -w a -w b --or-where c --or-where d  =>  (a) AND (b) AND ((c) OR (d))
+fn combine_where(m: &ArgMatches) -> String {
+    let paren = |s: &String| format!("({s})");
+    let mut clauses: Vec<String> = m.get_many::<String>("where").into_iter().flatten().map(paren).collect();
+    let any: Vec<String> = m.get_many::<String>("or-where").into_iter().flatten().map(paren).collect();
+    if !any.is_empty() {
+        clauses.push(format!("({})", any.join(" OR ")));
+    }
+    clauses.join(" AND ")
+}
+
 pub fn select_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
-    let cols = m.get_one::<String>("columns").unwrap();
     let output = m.get_one::<String>("output").unwrap();
-    let lf = infer_reader(input)?;
-    let df = lf.select(parse_cols_vec(cols)).collect()?;
-    write_df(&df, output)?;
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.collect_schema()?;
+    let available: Vec<&str> = schema.iter_names().map(|c| c.as_str()).collect();
+    // Without --columns everything is selected, so --exclude/--dtype alone narrow the full list
+    // cust_id:customer_id selects cust_id and renames it in the output
+    let mut renames: Vec<(String, String)> = vec![];
+    let mut names = match m.get_one::<String>("columns") {
+        Some(spec) => {
+            let plain: Vec<String> = parse_names(spec).into_iter()
+                .map(|t| match t.split_once(':') {
+                    Some((old, new)) if !t.starts_with("re:") => {
+                        renames.push((old.trim().to_string(), new.trim().to_string()));
+                        old.trim().to_string()
+                    }
+                    _ => t,
+                })
+                .collect();
+            resolve_columns(&plain.join(","), &available)?
+        }
+        None => available.iter().map(|c| c.to_string()).collect(),
+    };
+    if let Some(spec) = m.get_one::<String>("exclude") {
+        let drop = resolve_columns(spec, &available)?;
+        names.retain(|c| !drop.contains(c));
+    }
+    if let Some(kind) = m.get_one::<String>("dtype") {
+        names.retain(|c| schema.get(c).is_some_and(|dt| match kind.as_str() {
+            "numeric" => dt.is_numeric(),
+            "string" => matches!(dt, DataType::String),
+            "temporal" => dt.is_temporal(),
+            "boolean" => matches!(dt, DataType::Boolean),
+            _ => false,
+        }));
+    }
+    if names.is_empty() { bail!("No columns left to select"); }
+    let exprs: Vec<Expr> = names.iter()
+        .map(|c| match renames.iter().find(|(old, _)| old == c) {
+            Some((_, new)) => col(c.as_str()).alias(new.as_str()),
+            None => col(c.as_str()),
+        })
+        .collect();
+    let df = lf.select(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
     Ok(())
 }
 
 pub fn convert_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
     let output = m.get_one::<String>("output").unwrap();
-    let df = infer_reader(input)?.collect()?;
-    write_df(&df, output)?;
-    Ok(())
-}
-
-pub fn profile_cmd(m: &ArgMatches) -> Result<()> {
-    let input = m.get_one::<String>("input").unwrap();
-    let df = infer_reader(input)?.limit(1_000_000).collect()?;
-    println!("Rows(sampled): {}", df.height());
-    for s in df.get_columns() {
-        println!("- {}: {:?}, nulls={}", s.name(), s.dtype(), s.null_count());
-    }
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
     Ok(())
 }
 
 pub fn agg_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
-    let group = m.get_one::<String>("group").unwrap();
+    // --group a,b and --group a --group b are equivalent
+    let group: Vec<String> = m.get_many::<String>("group").into_iter().flatten().flat_map(|g| parse_names(g)).collect();
     let output = m.get_one::<String>("output").unwrap();
 
     let mut aggs: Vec<Expr> = vec![];
-    if let Some(vals) = m.get_many::<String>("sum") {
-        for v in vals { aggs.push(col(v).sum().alias(&format!("sum_{}", v))); }
-    }
-    if let Some(vals) = m.get_many::<String>("mean") {
-        for v in vals { aggs.push(col(v).mean().alias(&format!("mean_{}", v))); }
+    for agg in ["sum", "mean", "count", "min", "max", "median", "std", "first", "last", "n_unique"] {
+        // flags use dashes (--n-unique), output columns underscores (n_unique_x)
+        if let Some(vals) = m.get_many::<String>(&agg.replace('_', "-")) {
+            for v in vals { aggs.push(agg_expr(col(v), agg)?.alias(&format!("{agg}_{v}"))); }
+        }
     }
-    if let Some(vals) = m.get_many::<String>("count") {
-        for v in vals { aggs.push(col(v).count().alias(&format!("count_{}", v))); }
+    if let Some(vals) = m.get_many::<String>("quantile") {
+        for v in vals {
+            let Some((c, q)) = v.split_once(':') else { bail!("Invalid --quantile {v:?}; expected column:0.95"); };
+            let q: f64 = q.parse().map_err(|_| anyhow::anyhow!("Invalid quantile in {v:?}"))?;
+            if !(0.0..=1.0).contains(&q) { bail!("Quantile must be between 0 and 1, got {q}"); }
+            // 0.95 -> p95_amount, 0.995 -> p99_5_amount
+            let label = format!("{}", (q * 1000.0).round() / 10.0).replace('.', "_");
+            aggs.push(col(c).quantile(lit(q), QuantileInterpolOptions::Linear).alias(&format!("p{label}_{c}")));
+        }
     }
 
-    if aggs.is_empty() { bail!("No aggregations provided. Use --sum/--mean/--count."); }
+    if aggs.is_empty() { bail!("No aggregations provided. Use --sum/--mean/--count/--min/--max/--median/--std/--first/--last/--n-unique/--quantile."); }
 
-    let lf = infer_reader(input)?;
-    let df = lf.group_by([col(group)]).agg(aggs).collect()?;
-    write_df(&df, output)?;
-    Ok(())
-}
-
-pub fn join_cmd(m: &ArgMatches) -> Result<()> {
-    let left = m.get_one::<String>("left").unwrap();
-    let right = m.get_one::<String>("right").unwrap();
-    let on = m.get_one::<String>("on").unwrap();
-    let how = m.get_one::<String>("how").unwrap();
-    let output = m.get_one::<String>("output").unwrap();
-
-    let l = infer_reader(left)?;
-    let r = infer_reader(right)?;
-    let join_type = match how.as_str() {
-        "inner" => JoinType::Inner,
-        "left" => JoinType::Left,
-        other => bail!("Unsupported join how={}. Only 'inner' and 'left' are supported.", other),
-    };
-    let df = l.join_builder()
-        .with(r)
-        .left_on([col(on)])
-        .right_on([col(on)])
-        .how(join_type)
-        .finish().collect()?;
-    write_df(&df, output)?;
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let mut keys: Vec<Expr> = group.iter().map(|c| col(c.as_str())).collect();
+    let mut key_names = group.clone();
+    // order_ts:month groups on the timestamp truncated to the start of its month
+    for spec in m.get_many::<String>("group-by-date").into_iter().flatten() {
+        let Some((c, unit)) = spec.split_once(':') else { bail!("Invalid --group-by-date {spec:?}; expected column:unit"); };
+        let every = match unit {
+            "hour" => "1h",
+            "day" => "1d",
+            "week" => "1w",
+            "month" => "1mo",
+            "quarter" => "1q",
+            "year" => "1y",
+            other => bail!("Unsupported date unit: {other}. Use hour, day, week, month, quarter or year."),
+        };
+        keys.push(col(c).dt().truncate(lit(every)).alias(c));
+        key_names.push(c.to_string());
+    }
+    if keys.is_empty() { bail!("No grouping provided. Use --group and/or --group-by-date."); }
+    let pivot_on = m.get_one::<String>("pivot");
+    if let Some(p) = pivot_on {
+        keys.push(col(p.as_str()));
+    }
+    let value_names: Vec<String> = aggs.iter()
+        .map(|e| Ok(e.clone().meta().output_name()?.to_string()))
+        .collect::<Result<_>>()?;
+    let mut grouped = lf.group_by(keys).agg(aggs);
+    // HAVING sees the output names, e.g. "sum_amount > 1000"
+    if let Some(having) = m.get_one::<String>("having") {
+        grouped = grouped.filter(sql_expr(having)?);
+    }
+    let mut df = grouped.sort(key_names.clone(), SortMultipleOptions::default()).collect()?;
+    if let Some(p) = pivot_on {
+        // Each group/value pair holds exactly one aggregate, so `first` just spreads it out.
+        df = polars::prelude::pivot::pivot(
+            &df, [p.as_str()], Some(&key_names), Some(&value_names), true, Some(reshape::pivot_agg("first")?), Some("_"),
+        )?;
+        // A single aggregate is named by the pivot value alone; prefix it like the multi-value case.
+        if let [value] = value_names.as_slice() {
+            let spread: Vec<String> = df.get_column_names().iter()
+                .map(|c| c.to_string())
+                .filter(|c| !key_names.contains(c))
+                .collect();
+            for c in spread {
+                df.rename(&c, format!("{value}_{c}").as_str().into())?;
+            }
+        }
+    }
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
     Ok(())
 }
 
 // ----- Core planning helpers reused by PyO3 -----
-pub fn plan_filter(input: &str, where_expr: &str, select: Option<&String>) -> Result<LazyFrame> {
-    let lf = infer_reader(input)?;
-    let filtered = lf.filter(sql_expr(where_expr)?);
+pub fn plan_filter(input: &str, where_expr: &str, select: Option<&String>, ropts: &ReadOptions) -> Result<LazyFrame> {
+    let mut lf = infer_reader_with(input, ropts)?;
+    let predicate = sql_expr(where_expr)?;
+    let mut referenced: Vec<String> = predicate.clone().meta().root_names().iter().map(|c| c.to_string()).collect();
+    referenced.extend(select.map(|s| parse_names(s)).unwrap_or_default());
+    ensure_columns(&mut lf, &referenced)?;
+    let filtered = lf.filter(predicate);
     let lf = if let Some(sel) = select {
         filtered.select(parse_cols_vec(sel))
     } else { filtered };
     Ok(lf)
 }
 
+// Checked up front so a typo gets a readable message instead of a plan dump from Polars.
+fn ensure_columns(lf: &mut LazyFrame, names: &[String]) -> Result<()> {
+    let schema = lf.collect_schema()?;
+    let available: Vec<&str> = schema.iter_names().map(|c| c.as_str()).collect();
+    match names.iter().find(|n| !available.contains(&n.as_str())) {
+        Some(name) => Err(column_not_found(name, &available)),
+        None => Ok(()),
+    }
+}
+
+fn column_not_found(name: &str, available: &[&str]) -> anyhow::Error {
+    let hint = closest_name(name, available)
+        .map(|c| format!(" Did you mean `{c}`?"))
+        .unwrap_or_default();
+    anyhow::anyhow!("Column `{name}` not found.{hint}\nAvailable columns: {}", available.join(", "))
+}
+
+// Expands a comma-separated list of names, globs (`sales_*`) and regexes (`re:^f[0-9]+$`)
+// into column names, in the order requested and without duplicates.
+fn resolve_columns(spec: &str, available: &[&str]) -> Result<Vec<String>> {
+    let mut out: Vec<String> = vec![];
+    for token in parse_names(spec) {
+        let pattern = if let Some(re) = token.strip_prefix("re:") {
+            Some(re.to_string())
+        } else if token.contains(['*', '?']) {
+            Some(format!("^{}$", regex::escape(&token).replace(r"\*", ".*").replace(r"\?", ".")))
+        } else {
+            None
+        };
+        let matched: Vec<&str> = match pattern {
+            Some(p) => {
+                let re = regex::Regex::new(&p).map_err(|e| anyhow::anyhow!("Invalid column pattern {token:?}: {e}"))?;
+                let hits: Vec<&str> = available.iter().copied().filter(|c| re.is_match(c)).collect();
+                if hits.is_empty() { bail!("Pattern `{token}` matched no columns"); }
+                hits
+            }
+            None if available.contains(&token.as_str()) => vec![token.as_str()],
+            None => return Err(column_not_found(&token, available)),
+        };
+        for c in matched {
+            if !out.iter().any(|o| o == c) { out.push(c.to_string()); }
+        }
+    }
+    Ok(out)
+}
+
+fn closest_name<'a>(name: &str, available: &[&'a str]) -> Option<&'a str> {
+    let lower = name.to_lowercase();
+    available.iter()
+        .map(|c| (*c, strsim::levenshtein(&lower, &c.to_lowercase())))
+        // allow roughly one typo per three characters
+        .filter(|(_, d)| *d <= (name.chars().count() / 3).max(1))
+        .min_by_key(|(_, d)| *d)
+        .map(|(c, _)| c)
+}
+
 // Convenience APIs for Python bindings
 pub fn filter_to_path(input: &str, where_expr: &str, select: Option<&Vec<String>>, output: Option<&str>) -> Result<String> {
     let sel = select.map(|v| v.join(","));
-    let lf = plan_filter(input, where_expr, sel.as_ref());
+    let lf = plan_filter(input, where_expr, sel.as_ref(), &ReadOptions::default());
     let df = lf?.collect()?;
     let out = output.unwrap_or("dpa_out.parquet");
     crate::io::write_df(&df, out)?;
@@ -136,14 +344,3 @@ pub fn convert_to_path(input: &str, output: &str) -> Result<()> {
     crate::io::write_df(&df, output)?;
     Ok(())
 }
-
-pub fn profile_stats(input: &str) -> Result<std::collections::HashMap<String, String>> {
-    let df = infer_reader(input)?.limit(1_000_000).collect()?;
-    let mut m = std::collections::HashMap::new();
-    m.insert("rows".into(), df.height().to_string());
-    for s in df.get_columns() {
-        m.insert(format!("dtype:{}", s.name()), format!("{:?}", s.dtype()));
-        m.insert(format!("nulls:{}", s.name()), s.null_count().to_string());
-    }
-    Ok(m)
-}
@@ -0,0 +1,248 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::io::{dtype_name, infer_reader, infer_reader_with, ReadOptions};
+
+// Profiles look at the first million rows so they stay quick on large files.
+const PROFILE_ROWS: IdxSize = 1_000_000;
+const HISTOGRAM_BINS: usize = 10;
+const TOP_VALUES: usize = 10;
+
+/// The full profile; `--format json` serializes it as-is.
+#[derive(Debug, Serialize)]
+pub struct ProfileReport {
+    input: String,
+    rows_sampled: usize,
+    column_count: usize,
+    missing_cells: usize,
+    missing_pct: f64,
+    duplicate_rows: usize,
+    columns: Vec<ColumnProfile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ColumnProfile {
+    name: String,
+    dtype: String,
+    count: usize,
+    nulls: usize,
+    null_pct: f64,
+    unique: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    numeric: Option<NumericStats>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    top_values: Vec<ValueCount>,
+}
+
+#[derive(Debug, Serialize)]
+struct NumericStats {
+    mean: Option<f64>,
+    std: Option<f64>,
+    min: Option<f64>,
+    p25: Option<f64>,
+    p50: Option<f64>,
+    p75: Option<f64>,
+    max: Option<f64>,
+    histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize)]
+struct HistogramBin {
+    start: f64,
+    end: f64,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ValueCount {
+    value: String,
+    count: usize,
+}
+
+pub fn profile_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let format = m.get_one::<String>("format").unwrap();
+    let output = m.get_one::<String>("output");
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.limit(PROFILE_ROWS).collect()?;
+    let text = match format.as_str() {
+        "text" => {
+            let mut text = format!("Rows(sampled): {}\n", df.height());
+            for s in df.get_columns() {
+                text.push_str(&format!("- {}: {:?}, nulls={}\n", s.name(), s.dtype(), s.null_count()));
+            }
+            text
+        }
+        "json" => serde_json::to_string_pretty(&profile_report(input, &df)?)? + "\n",
+        "html" => render_html(&profile_report(input, &df)?),
+        other => bail!("Unsupported format={}. Use text, json or html.", other),
+    };
+    match output {
+        Some(path) => {
+            if !m.get_flag("force") && Path::new(path).exists() {
+                bail!("Output {path} already exists; pass --force to overwrite it");
+            }
+            std::fs::write(path, text)?;
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+pub fn profile_report(input: &str, df: &DataFrame) -> Result<ProfileReport> {
+    let columns = df.get_columns().iter().map(column_profile).collect::<Result<Vec<_>>>()?;
+    let missing_cells: usize = columns.iter().map(|c| c.nulls).sum();
+    let cells = (df.height() * df.width()).max(1);
+    let distinct = df.clone().lazy().unique(None, UniqueKeepStrategy::Any).select([len()]).collect()?;
+    let distinct = distinct.column("len")?.idx()?.get(0).unwrap_or(0) as usize;
+    Ok(ProfileReport {
+        input: input.to_string(),
+        rows_sampled: df.height(),
+        column_count: df.width(),
+        missing_cells,
+        missing_pct: 100.0 * missing_cells as f64 / cells as f64,
+        duplicate_rows: df.height() - distinct,
+        columns,
+    })
+}
+
+fn column_profile(s: &Series) -> Result<ColumnProfile> {
+    let nulls = s.null_count();
+    let numeric = if s.dtype().is_numeric() { Some(numeric_stats(s)?) } else { None };
+    let top_values = if numeric.is_none() { top_values(s)? } else { vec![] };
+    Ok(ColumnProfile {
+        name: s.name().to_string(),
+        dtype: dtype_name(s.dtype()),
+        count: s.len() - nulls,
+        nulls,
+        null_pct: if s.is_empty() { 0.0 } else { 100.0 * nulls as f64 / s.len() as f64 },
+        unique: s.n_unique()?,
+        numeric,
+        top_values,
+    })
+}
+
+fn numeric_stats(s: &Series) -> Result<NumericStats> {
+    let cast = s.cast(&DataType::Float64)?;
+    let ca = cast.f64()?;
+    let q = |p: f64| ca.quantile(p, QuantileInterpolOptions::Linear);
+    let (min, max) = (ca.min(), ca.max());
+    let mut histogram = vec![];
+    if let (Some(lo), Some(hi)) = (min, max) {
+        // A constant column gets one bin instead of ten empty-width ones.
+        let bins = if hi > lo { HISTOGRAM_BINS } else { 1 };
+        let width = (hi - lo) / bins as f64;
+        let mut counts = vec![0usize; bins];
+        for v in ca.into_iter().flatten() {
+            let i = if width > 0.0 { ((v - lo) / width) as usize } else { 0 };
+            counts[i.min(bins - 1)] += 1;
+        }
+        histogram = counts.into_iter().enumerate()
+            .map(|(i, count)| HistogramBin { start: lo + width * i as f64, end: lo + width * (i + 1) as f64, count })
+            .collect();
+    }
+    Ok(NumericStats {
+        mean: ca.mean(),
+        std: ca.std(1),
+        min,
+        p25: q(0.25)?,
+        p50: q(0.5)?,
+        p75: q(0.75)?,
+        max,
+        histogram,
+    })
+}
+
+// Most frequent values first; ties resolve to the smaller value for stable output
+fn top_values(s: &Series) -> Result<Vec<ValueCount>> {
+    let cast = s.cast(&DataType::String)?;
+    let mut freq: HashMap<&str, usize> = HashMap::new();
+    for v in cast.str()?.into_iter().flatten() {
+        *freq.entry(v).or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = freq.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    Ok(counts.into_iter().take(TOP_VALUES)
+        .map(|(value, count)| ValueCount { value: value.to_string(), count })
+        .collect())
+}
+
+// Self-contained page: inline CSS, histograms and top values drawn as plain bars, no scripts.
+fn render_html(r: &ProfileReport) -> String {
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Profile: {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape(&r.input), REPORT_CSS,
+    ));
+    html.push_str(&format!("<h1>Profile: {}</h1>\n", escape(&r.input)));
+    html.push_str("<h2>Quality summary</h2>\n<table class=\"summary\">\n");
+    for (label, value) in [
+        ("Rows (sampled)", r.rows_sampled.to_string()),
+        ("Columns", r.column_count.to_string()),
+        ("Missing cells", format!("{} ({:.2}%)", r.missing_cells, r.missing_pct)),
+        ("Duplicate rows", r.duplicate_rows.to_string()),
+    ] {
+        html.push_str(&format!("<tr><th>{label}</th><td>{value}</td></tr>\n"));
+    }
+    html.push_str("</table>\n<h2>Columns</h2>\n<table>\n<tr><th>Name</th><th>Type</th><th>Non-null</th><th>Nulls</th><th>Unique</th><th>Mean</th><th>Min</th><th>Max</th></tr>\n");
+    // Anchors use the column position; names may be anything and can collide once sanitized.
+    for (i, c) in r.columns.iter().enumerate() {
+        let stat = |f: fn(&NumericStats) -> Option<f64>| c.numeric.as_ref().and_then(f).map(fmt_stat).unwrap_or_default();
+        html.push_str(&format!(
+            "<tr><td><a href=\"#col-{i}\">{name}</a></td><td>{}</td><td>{}</td><td class=\"{}\">{} ({:.1}%)</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&c.dtype), c.count, if c.nulls > 0 { "warn" } else { "" }, c.nulls, c.null_pct, c.unique,
+            stat(|n| n.mean), stat(|n| n.min), stat(|n| n.max),
+            name = escape(&c.name),
+        ));
+    }
+    html.push_str("</table>\n<h2>Distributions</h2>\n");
+    for (i, c) in r.columns.iter().enumerate() {
+        html.push_str(&format!("<section id=\"col-{i}\">\n<h3>{} <small>{}</small></h3>\n", escape(&c.name), escape(&c.dtype)));
+        let bars: Vec<(String, usize)> = match &c.numeric {
+            Some(n) => n.histogram.iter().map(|b| (format!("{} – {}", fmt_stat(b.start), fmt_stat(b.end)), b.count)).collect(),
+            None => c.top_values.iter().map(|v| (escape(&v.value), v.count)).collect(),
+        };
+        let peak = bars.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1);
+        html.push_str("<table class=\"bars\">\n");
+        for (label, n) in &bars {
+            html.push_str(&format!(
+                "<tr><td>{label}</td><td class=\"bar\"><div style=\"width:{:.1}%\"></div></td><td>{n}</td></tr>\n",
+                100.0 * *n as f64 / peak as f64,
+            ));
+        }
+        html.push_str("</table>\n</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const REPORT_CSS: &str = "body{font-family:system-ui,sans-serif;margin:2rem;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5rem}\
+th,td{border:1px solid #ddd;padding:4px 8px;text-align:left}\
+th{background:#f4f4f4}\
+td.warn{color:#b00}\
+table.bars td.bar{width:320px}\
+table.bars td.bar div{background:#4a7bd0;height:12px}\
+small{color:#777;font-weight:normal}";
+
+fn fmt_stat(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 { format!("{v:.0}") } else { format!("{v:.4}") }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub fn profile_stats(input: &str) -> Result<HashMap<String, String>> {
+    let df = infer_reader(input)?.limit(PROFILE_ROWS).collect()?;
+    let mut m = HashMap::new();
+    m.insert("rows".into(), df.height().to_string());
+    for s in df.get_columns() {
+        m.insert(format!("dtype:{}", s.name()), format!("{:?}", s.dtype()));
+        m.insert(format!("nulls:{}", s.name()), s.null_count().to_string());
+    }
+    Ok(m)
+}
@@ -0,0 +1,202 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use polars::prelude::pivot::pivot;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::parse_names;
+
+pub fn pivot_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let index = parse_names(m.get_one::<String>("index").unwrap());
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let values = parse_names(m.get_one::<String>("values").unwrap());
+    let agg = m.get_one::<String>("agg").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let wide = pivot(&df, &columns, Some(&index), Some(&values), true, Some(pivot_agg(agg)?), None)?;
+    write_df_with(&wide, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn melt_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let id_vars = parse_names(m.get_one::<String>("id-vars").unwrap());
+    // Without --value-vars every non-id column is unpivoted.
+    let value_vars = m.get_one::<String>("value-vars").map(|s| parse_names(s)).unwrap_or_default();
+    let variable_name = m.get_one::<String>("variable-name").unwrap();
+    let value_name = m.get_one::<String>("value-name").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let mut long = df.unpivot(value_vars, id_vars)?;
+    long.rename("variable", variable_name.as_str().into())?;
+    long.rename("value", value_name.as_str().into())?;
+    write_df_with(&long, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn concat_cmd(m: &ArgMatches) -> Result<()> {
+    let inputs: Vec<&String> = m.get_many::<String>("inputs").unwrap().collect();
+    let how = m.get_one::<String>("how").unwrap();
+    let source_column = m.get_one::<String>("source-column");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let ropts = ReadOptions::from_matches(m);
+    let mut frames = vec![];
+    for input in inputs {
+        let lf = infer_reader_with(input, &ropts)?;
+        frames.push(match source_column {
+            Some(name) => lf.with_column(lit(input.as_str()).alias(name.as_str())),
+            None => lf,
+        });
+    }
+    let lf = match how.as_str() {
+        "vertical" => concat(frames, UnionArgs::default())?,
+        // diagonal fills columns missing from some inputs with nulls
+        "diagonal" => concat_lf_diagonal(frames, UnionArgs::default())?,
+        other => bail!("Unsupported how={}. Use vertical or diagonal.", other),
+    };
+    let df = lf.collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn explode_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.explode(columns.iter().map(|c| col(c.as_str())).collect::<Vec<_>>()).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn unnest_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let sep = m.get_one::<String>("separator").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.clone().collect_schema()?;
+    for c in &columns {
+        match schema.get(c.as_str()) {
+            Some(DataType::Struct(_)) => {}
+            Some(other) => bail!("Column {c} is {other:?}, not a struct"),
+            None => bail!("Column not found: {c}"),
+        }
+    }
+
+    // Struct fields take the struct column's position, prefixed with its
+    // name so they can't collide with existing top-level columns.
+    let mut exprs = vec![];
+    for (name, dt) in schema.iter() {
+        match dt {
+            DataType::Struct(fields) if columns.iter().any(|c| c == name.as_str()) => {
+                for f in fields {
+                    exprs.push(col(name.as_str()).struct_().field_by_name(f.name())
+                        .alias(&format!("{name}{sep}{}", f.name())));
+                }
+            }
+            _ => exprs.push(col(name.as_str())),
+        }
+    }
+    let df = lf.select(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn transpose_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let header_column = m.get_one::<String>("header-column");
+    let names_as = m.get_one::<String>("names-as").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    // Values of --header-column become the new column names.
+    let headers: Option<Vec<String>> = match header_column {
+        Some(h) => {
+            let s = df.drop_in_place(h)?.cast(&DataType::String)?;
+            Some(s.str()?.into_iter().map(|v| v.unwrap_or("null").to_string()).collect())
+        }
+        None => None,
+    };
+    // Mixed dtypes can't share one output column, so fall back to strings.
+    let first = df.dtypes().first().cloned();
+    if df.dtypes().iter().any(|dt| Some(dt) != first.as_ref()) {
+        df = df.lazy().select([col("*").cast(DataType::String)]).collect()?;
+    }
+    let mut out = df.transpose(Some(names_as.as_str()), None)?;
+    if let Some(headers) = headers {
+        let old: Vec<String> = out.get_column_names().iter().skip(1).map(|c| c.to_string()).collect();
+        for (o, n) in old.iter().zip(&headers) {
+            out.rename(o, n.as_str().into())?;
+        }
+    }
+    write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn crosstab_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let rows = parse_names(m.get_one::<String>("rows").unwrap());
+    let cols = m.get_one::<String>("cols").unwrap();
+    let normalize = m.get_one::<String>("normalize");
+    let output = m.get_one::<String>("output");
+
+    let mut keys: Vec<Expr> = rows.iter().map(|c| col(c.as_str())).collect();
+    keys.push(col(cols.as_str()));
+    let counts = infer_reader_with(input, &ReadOptions::from_matches(m))?
+        .group_by(keys)
+        .agg([len().alias("count")])
+        .collect()?;
+    let wide = pivot(&counts, [cols.as_str()], Some(&rows), Some(["count"]), true, Some(PivotAgg::Sum), None)?;
+
+    let cells: Vec<String> = wide.get_column_names().iter()
+        .map(|c| c.to_string())
+        .filter(|c| !rows.contains(c))
+        .collect();
+    let cell = |c: &String| col(c.as_str()).fill_null(lit(0)).cast(DataType::Float64);
+    let sum = |exprs: Vec<Expr>| exprs.into_iter().reduce(|a, b| a + b).unwrap_or(lit(0.0));
+    // Percentages of the row total, the column total, or the grand total.
+    let total = match normalize.map(String::as_str) {
+        None => None,
+        Some("row") => Some(sum(cells.iter().map(cell).collect())),
+        Some("all") => Some(sum(cells.iter().map(|c| cell(c).sum()).collect())),
+        Some("col") => None,
+        Some(other) => bail!("Unsupported normalize={}. Use row, col or all.", other),
+    };
+    let exprs = cells.iter().map(|c| {
+        let e = match (normalize.map(String::as_str), &total) {
+            (Some("col"), _) => cell(c) * lit(100.0) / cell(c).sum(),
+            (_, Some(t)) => cell(c) * lit(100.0) / t.clone(),
+            _ => col(c.as_str()).fill_null(lit(0)),
+        };
+        e.alias(c.as_str())
+    }).collect::<Vec<_>>();
+    let df = wide.lazy()
+        .with_columns(exprs)
+        .sort(rows.clone(), SortMultipleOptions::default())
+        .collect()?;
+    match output {
+        Some(out) => write_df_with(&df, out, &WriteOptions::from_matches(m))?,
+        None => println!("{df}"),
+    }
+    Ok(())
+}
+
+pub(crate) fn pivot_agg(name: &str) -> Result<PivotAgg> {
+    Ok(match name {
+        "sum" => PivotAgg::Sum,
+        "mean" => PivotAgg::Mean,
+        "median" => PivotAgg::Median,
+        "min" => PivotAgg::Min,
+        "max" => PivotAgg::Max,
+        "count" => PivotAgg::Count,
+        "first" => PivotAgg::First,
+        "last" => PivotAgg::Last,
+        other => bail!("Unsupported pivot aggregation: {other}"),
+    })
+}
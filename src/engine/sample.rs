@@ -0,0 +1,239 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::io::{write_df_with, infer_reader, infer_reader_with, ReadOptions, WriteOptions};
+
+pub fn sample_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let method = m.get_one::<String>("method").unwrap();
+    let stratify = m.get_one::<String>("stratify");
+    let seed = m.get_one::<u64>("seed").copied();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if method == "systematic" {
+        let Some(every) = m.get_one::<usize>("every").copied() else { bail!("--method systematic needs --every <n>"); };
+        let offset = m.get_one::<usize>("offset").copied().unwrap_or(0);
+        let out = sample_systematic(lf, every, offset)?;
+        write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+        return Ok(());
+    }
+    let size = m.get_one::<usize>("size").copied();
+    let fraction = m.get_one::<f64>("fraction").copied();
+    if let Some(f) = fraction {
+        if !(0.0..=1.0).contains(&f) { bail!("--fraction must be between 0 and 1, got {f}"); }
+    }
+    if method == "cluster" {
+        let Some(c) = m.get_one::<String>("cluster-column") else { bail!("--method cluster needs --cluster-column <column>"); };
+        let clusters = m.get_one::<usize>("clusters").copied();
+        if clusters.is_none() && fraction.is_none() { bail!("--method cluster needs --clusters <n> or --fraction <f>"); }
+        let out = sample_cluster(lf, c, clusters, fraction, seed)?;
+        write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+        return Ok(());
+    }
+    if method == "reservoir" {
+        let out = match (size, fraction) {
+            (Some(n), _) => sample_reservoir(lf, n, seed)?,
+            // The row count isn't known while streaming, so each row is kept with probability f
+            (None, Some(f)) => sample_bernoulli(lf, f, seed)?,
+            (None, None) => bail!("--method reservoir needs --size <n> or --fraction <f>"),
+        };
+        write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+        return Ok(());
+    }
+    let df = lf.collect()?;
+    if method == "bootstrap" {
+        // Classic bootstrap resamples as many rows as the input unless told otherwise
+        let n = rows_for(size, fraction, df.height()).unwrap_or(df.height());
+        let resamples = m.get_one::<usize>("resamples").copied().unwrap_or(1);
+        let wopts = WriteOptions::from_matches(m);
+        for i in 0..resamples {
+            let out = sample_bootstrap(&df, n, seed.map(|s| s.wrapping_add(i as u64)))?;
+            let path = if resamples > 1 { numbered_path(output, i) } else { output.clone() };
+            write_df_with(&out, &path, &wopts)?;
+        }
+        return Ok(());
+    }
+    let Some(size) = rows_for(size, fraction, df.height()) else { bail!("--method {method} needs --size <n> or --fraction <f>"); };
+    let out = match (method.as_str(), stratify) {
+        ("random", _) => sample_random(&df, size, seed)?,
+        ("stratified", Some(c)) => sample_stratified(&df, c, size, seed)?,
+        ("stratified", None) => bail!("--method stratified needs --stratify <column>"),
+        ("weighted", _) => {
+            let Some(w) = m.get_one::<String>("weight-column") else { bail!("--method weighted needs --weight-column <column>"); };
+            sample_weighted(&df, w, size, m.get_flag("replace"), seed)?
+        }
+        (other, _) => bail!("Unsupported method={}. Use random, reservoir, stratified, systematic, weighted, cluster or bootstrap.", other),
+    };
+    write_df_with(&out, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+/// `--size` as given, otherwise `--fraction` of `height` rounded to whole rows.
+fn rows_for(size: Option<usize>, fraction: Option<f64>, height: usize) -> Option<usize> {
+    size.or_else(|| fraction.map(|f| (f * height as f64).round() as usize))
+}
+
+/// Uniform sample of `n` rows without replacement (all rows if `n` exceeds the height).
+pub fn sample_random(df: &DataFrame, n: usize, seed: Option<u64>) -> Result<DataFrame> {
+    Ok(df.sample_n_literal(n.min(df.height()), false, false, seed)?)
+}
+
+/// Uniform sample of `n` rows in one streaming pass, for inputs too large to collect.
+///
+/// Every row gets a seeded pseudo-random key and the `n` smallest keys are kept, which is the
+/// reservoir idea expressed as a bounded top-k: memory holds `n` rows, not the file.
+/// Rows come back in input order.
+pub fn sample_reservoir(lf: LazyFrame, n: usize, seed: Option<u64>) -> Result<DataFrame> {
+    let seed = seed.unwrap_or_else(random_seed);
+    let df = lf
+        .with_row_index("__row", None)
+        .with_column(col("__row").hash(seed, 0, 0, 0).alias("__key"))
+        .bottom_k(n as IdxSize, [col("__key")], SortMultipleOptions::default())
+        .sort(["__row"], SortMultipleOptions::default())
+        .drop(["__row", "__key"])
+        .with_streaming(true)
+        .collect()?;
+    Ok(df)
+}
+
+/// Keeps each row independently with probability `fraction`, streaming the input.
+pub fn sample_bernoulli(lf: LazyFrame, fraction: f64, seed: Option<u64>) -> Result<DataFrame> {
+    let seed = seed.unwrap_or_else(random_seed);
+    // Row hashes are uniform over u64, so comparing against a scaled cutoff keeps ~fraction of rows
+    let cutoff = (fraction * u64::MAX as f64) as u64;
+    let df = lf
+        .with_row_index("__row", None)
+        .filter(col("__row").hash(seed, 0, 0, 0).lt(lit(cutoff)))
+        .drop(["__row"])
+        .with_streaming(true)
+        .collect()?;
+    Ok(df)
+}
+
+/// Picks whole groups of `column` at random (`clusters` of them, or `fraction` of all groups)
+/// and keeps every row of the chosen groups, so per-user sequences stay intact.
+pub fn sample_cluster(lf: LazyFrame, column: &str, clusters: Option<usize>, fraction: Option<f64>, seed: Option<u64>) -> Result<DataFrame> {
+    let ids = lf.clone()
+        .select([col(column)])
+        .unique_stable(None, UniqueKeepStrategy::First)
+        .collect()?;
+    let n = rows_for(clusters, fraction, ids.height()).unwrap_or(0);
+    let chosen = sample_random(&ids, n, seed)?;
+    let df = lf
+        .join(chosen.lazy(), [col(column)], [col(column)], JoinArgs::new(JoinType::Semi))
+        .collect()?;
+    Ok(df)
+}
+
+/// `n` rows drawn with replacement; `n` may exceed the input height.
+pub fn sample_bootstrap(df: &DataFrame, n: usize, seed: Option<u64>) -> Result<DataFrame> {
+    if df.height() == 0 { return Ok(df.clear()); }
+    Ok(df.sample_n_literal(n, true, false, seed)?)
+}
+
+// boot.parquet -> boot_0003.parquet
+fn numbered_path(output: &str, i: usize) -> String {
+    let path = std::path::Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("sample");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{stem}_{i:04}.{ext}"),
+        None => format!("{stem}_{i:04}"),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Every `every`-th row starting at row `offset`, e.g. rows 5, 105, 205, ... for 100/5.
+pub fn sample_systematic(lf: LazyFrame, every: usize, offset: usize) -> Result<DataFrame> {
+    if every == 0 { bail!("--every must be at least 1"); }
+    Ok(lf.select([all().gather_every(every, offset)]).collect()?)
+}
+
+/// Draws `n` rows with probability proportional to `weight_column`; null weights count as zero.
+///
+/// Without replacement this uses Efraimidis-Spirakis keys (`u^(1/w)`, largest `n` win), so a
+/// row never repeats and zero-weight rows are never picked.
+pub fn sample_weighted(df: &DataFrame, weight_column: &str, n: usize, replace: bool, seed: Option<u64>) -> Result<DataFrame> {
+    let weights: Vec<f64> = df.column(weight_column)?.cast(&DataType::Float64)?
+        .f64()?.into_iter().map(|w| w.unwrap_or(0.0)).collect();
+    if weights.iter().any(|w| *w < 0.0 || !w.is_finite()) {
+        bail!("Weights in {weight_column} must be finite and non-negative");
+    }
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 { bail!("Weights in {weight_column} sum to zero"); }
+    let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(random_seed));
+
+    let idx: Vec<IdxSize> = if replace {
+        let mut cumulative = Vec::with_capacity(weights.len());
+        let mut acc = 0.0;
+        for w in &weights {
+            acc += w;
+            cumulative.push(acc);
+        }
+        (0..n).map(|_| {
+            let target = rng.gen::<f64>() * total;
+            cumulative.partition_point(|c| *c <= target).min(weights.len() - 1) as IdxSize
+        }).collect()
+    } else {
+        let mut keyed: Vec<(f64, IdxSize)> = weights.iter().enumerate()
+            .filter(|(_, w)| **w > 0.0)
+            .map(|(i, w)| (rng.gen::<f64>().powf(1.0 / w), i as IdxSize))
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+        keyed.truncate(n);
+        // keep the input order of the chosen rows
+        let mut idx: Vec<IdxSize> = keyed.into_iter().map(|(_, i)| i).collect();
+        idx.sort_unstable();
+        idx
+    };
+    Ok(df.take(&IdxCa::from_vec("idx".into(), idx))?)
+}
+
+/// Seed for runs without `--seed`, taken from the clock.
+pub(crate) fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// Samples each group of `column` in proportion to its share of the rows.
+pub fn sample_stratified(df: &DataFrame, column: &str, n: usize, seed: Option<u64>) -> Result<DataFrame> {
+    let total = df.height().max(1) as f64;
+    let mut out: Option<DataFrame> = None;
+    for (i, group) in df.partition_by_stable([column], true)?.iter().enumerate() {
+        let take = (n as f64 * group.height() as f64 / total).round() as usize;
+        // Offset the seed per group so equal-sized groups don't pick the same positions.
+        let part = sample_random(group, take, seed.map(|s| s.wrapping_add(i as u64)))?;
+        match out.as_mut() {
+            Some(acc) => { acc.vstack_mut(&part)?; }
+            None => out = Some(part),
+        }
+    }
+    Ok(out.unwrap_or_else(|| df.clear()))
+}
+
+pub fn sample_to_path(input: &str, size: Option<usize>, fraction: Option<f64>, seed: Option<u64>, stratify: Option<&str>, output: Option<&str>) -> Result<String> {
+    let df = infer_reader(input)?.collect()?;
+    let Some(size) = rows_for(size, fraction, df.height()) else { bail!("Pass either size or fraction"); };
+    let out_df = match stratify {
+        Some(c) => sample_stratified(&df, c, size, seed)?,
+        None => sample_random(&df, size, seed)?,
+    };
+    let out = output.unwrap_or("dpa_out.parquet");
+    crate::io::write_df(&out_df, out)?;
+    Ok(out.to_string())
+}
+
+pub fn shuffle_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let seed = m.get_one::<u64>("seed").copied();
+    let output = m.get_one::<String>("output").unwrap();
+
+    // Sampling every row without replacement is a full permutation.
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let df = df.sample_n_literal(df.height(), false, true, seed)?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
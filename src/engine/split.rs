@@ -0,0 +1,247 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use chrono::{NaiveDate, NaiveDateTime};
+use polars::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::io::{is_fold_dir, prepare_output_dir, write_df_with, infer_reader, infer_reader_with, ReadOptions, WriteOptions};
+use super::sample::random_seed;
+
+pub fn split_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let method = m.get_one::<String>("method").unwrap();
+    let test_size = *m.get_one::<f64>("test-size").unwrap();
+    let stratify = m.get_one::<String>("stratify");
+    // Always split with a concrete seed and print it, so an unseeded run can be reproduced
+    let seed = m.get_one::<u64>("seed").copied().unwrap_or_else(random_seed);
+    let train_out = m.get_one::<String>("train").unwrap();
+    let test_out = m.get_one::<String>("test").unwrap();
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let (train, test) = match method.as_str() {
+        "time" => {
+            let Some(c) = m.get_one::<String>("time-column") else { bail!("--method time needs --time-column <column>"); };
+            split_time(&df, c, m.get_one::<String>("cutoff").map(String::as_str), test_size)?
+        }
+        _ => match m.get_one::<String>("group-column") {
+            Some(g) => split_grouped(&df, g, test_size, Some(seed))?,
+            None => match (stratify, m.get_one::<usize>("stratify-bins")) {
+                (Some(c), Some(bins)) => split_binned(&df, c, *bins, test_size, Some(seed))?,
+                _ => split_frame(&df, test_size, stratify.map(String::as_str), Some(seed))?,
+            },
+        },
+    };
+    let wopts = WriteOptions::from_matches(m);
+    write_df_with(&train, train_out, &wopts)?;
+    write_df_with(&test, test_out, &wopts)?;
+    if method == "time" {
+        println!("train: {} rows, test: {} rows", train.height(), test.height());
+    } else {
+        println!("train: {} rows, test: {} rows, seed: {seed}", train.height(), test.height());
+    }
+    Ok(())
+}
+
+fn split_frame(df: &DataFrame, test_size: f64, stratify: Option<&str>, seed: Option<u64>) -> Result<(DataFrame, DataFrame)> {
+    if !(0.0..=1.0).contains(&test_size) { bail!("--test-size must be between 0 and 1"); }
+    match stratify {
+        Some(c) => split_stratified(df, c, test_size, seed),
+        None => split_random(df, test_size, seed),
+    }
+}
+
+/// Shuffles once and cuts the first `test_size` share off as the test set, so the two sides
+/// are disjoint and together hold every row whether or not a seed is given.
+pub fn split_random(df: &DataFrame, test_size: f64, seed: Option<u64>) -> Result<(DataFrame, DataFrame)> {
+    let shuffled = df.sample_n_literal(df.height(), false, true, seed)?;
+    let n_test = (df.height() as f64 * test_size).round() as usize;
+    let test = shuffled.slice(0, n_test);
+    let train = shuffled.slice(n_test as i64, df.height() - n_test);
+    Ok((train, test))
+}
+
+/// Stratifies on a continuous `column` by first cutting it into `bins` equal-count quantile bins.
+pub fn split_binned(df: &DataFrame, column: &str, bins: usize, test_size: f64, seed: Option<u64>) -> Result<(DataFrame, DataFrame)> {
+    if bins == 0 { bail!("--stratify-bins must be at least 1"); }
+    if !(0.0..=1.0).contains(&test_size) { bail!("--test-size must be between 0 and 1"); }
+    // bin = floor((rank - 1) * bins / non-null count); null values form their own stratum
+    let rank = col(column).rank(RankOptions { method: RankMethod::Ordinal, descending: false }, None);
+    let binned = df.clone().lazy()
+        .with_column(
+            ((rank.cast(DataType::Float64) - lit(1.0)) * lit(bins as f64) / col(column).count().cast(DataType::Float64))
+                .floor()
+                .cast(DataType::UInt32)
+                .alias("__stratum"),
+        )
+        .collect()?;
+    let (train, test) = split_stratified(&binned, "__stratum", test_size, seed)?;
+    Ok((train.drop("__stratum")?, test.drop("__stratum")?))
+}
+
+/// Sends whole groups of `column` to one side: a `test_size` share of the distinct groups is
+/// drawn for test and every row of those groups follows, so no entity appears in both sets.
+pub fn split_grouped(df: &DataFrame, column: &str, test_size: f64, seed: Option<u64>) -> Result<(DataFrame, DataFrame)> {
+    if !(0.0..=1.0).contains(&test_size) { bail!("--test-size must be between 0 and 1"); }
+    let groups = df.clone().lazy()
+        .select([col(column)])
+        .unique_stable(None, UniqueKeepStrategy::First)
+        .collect()?;
+    let (_, test_groups) = split_random(&groups, test_size, seed)?;
+    let side = |how: JoinType| df.clone().lazy()
+        .join(test_groups.clone().lazy(), [col(column)], [col(column)], JoinArgs::new(how))
+        .collect();
+    Ok((side(JoinType::Anti)?, side(JoinType::Semi)?))
+}
+
+/// Puts rows at or after `cutoff` (or the latest `test_size` share of rows) in test and the
+/// rest in train, so no training row is newer than a test row. Rows with a null time train.
+pub fn split_time(df: &DataFrame, column: &str, cutoff: Option<&str>, test_size: f64) -> Result<(DataFrame, DataFrame)> {
+    let Some(cutoff) = cutoff else {
+        if !(0.0..=1.0).contains(&test_size) { bail!("--test-size must be between 0 and 1"); }
+        // nulls sort first, so they stay on the train side
+        let sorted = df.sort([column], SortMultipleOptions::default())?;
+        let n_test = (df.height() as f64 * test_size).round() as usize;
+        let n_train = df.height() - n_test;
+        return Ok((sorted.slice(0, n_train), sorted.slice(n_train as i64, n_test)));
+    };
+    let cut = cutoff_literal(df.column(column)?.dtype(), cutoff)?;
+    let is_test = col(column).gt_eq(cut);
+    let train = df.clone().lazy().filter(is_test.clone().not().or(col(column).is_null())).collect()?;
+    let test = df.clone().lazy().filter(is_test).collect()?;
+    Ok((train, test))
+}
+
+// 2024-01-01 or 2024-01-01 12:00:00 against date/datetime columns, plain numbers against numeric ones
+fn cutoff_literal(dtype: &DataType, cutoff: &str) -> Result<Expr> {
+    let date = || NaiveDate::parse_from_str(cutoff, "%Y-%m-%d").ok();
+    let datetime = || ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"].iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(cutoff, f).ok())
+        .or_else(|| date().and_then(|d| d.and_hms_opt(0, 0, 0)));
+    let bad = || anyhow::anyhow!("Cannot compare --cutoff {cutoff:?} with a {dtype} column");
+    Ok(match dtype {
+        DataType::Date => lit(date().ok_or_else(bad)?),
+        DataType::Datetime(_, _) => lit(datetime().ok_or_else(bad)?).cast(dtype.clone()),
+        // ISO timestamps stored as text still order correctly as strings
+        DataType::String => lit(cutoff),
+        dt if dt.is_numeric() => lit(cutoff.parse::<f64>().map_err(|_| bad())?),
+        _ => return Err(bad()),
+    })
+}
+
+/// Splits each class of `column` separately so both sides keep its distribution.
+pub fn split_stratified(df: &DataFrame, column: &str, test_size: f64, seed: Option<u64>) -> Result<(DataFrame, DataFrame)> {
+    let (mut train, mut test) = (df.clear(), df.clear());
+    for (i, group) in df.partition_by_stable([column], true)?.iter().enumerate() {
+        let (tr, te) = split_random(group, test_size, seed.map(|s| s.wrapping_add(i as u64)))?;
+        train.vstack_mut(&tr)?;
+        test.vstack_mut(&te)?;
+    }
+    Ok((train, test))
+}
+
+pub fn split_to_paths(input: &str, test_size: f64, seed: Option<u64>, stratify: Option<&str>, train_out: &str, test_out: &str) -> Result<()> {
+    let df = infer_reader(input)?.collect()?;
+    let seed = seed.unwrap_or_else(random_seed);
+    let (train, test) = split_frame(&df, test_size, stratify, Some(seed))?;
+    crate::io::write_df(&train, train_out)?;
+    crate::io::write_df(&test, test_out)?;
+    Ok(())
+}
+
+pub fn kfold_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let folds = *m.get_one::<usize>("folds").unwrap();
+    let stratify = m.get_one::<String>("stratify");
+    let seed = m.get_one::<u64>("seed").copied().unwrap_or_else(random_seed);
+    let output = m.get_one::<String>("output").unwrap();
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let assigned = assign_folds(&df, folds, stratify.map(String::as_str), seed)?;
+    let wopts = WriteOptions::from_matches(m);
+    if m.get_flag("assignments") {
+        // one file, every row tagged with the fold it is tested in
+        write_df_with(&assigned, output, &wopts)?;
+    } else {
+        let ext = wopts.format.clone().unwrap_or_else(|| "parquet".into());
+        let part_opts = WriteOptions { format: Some(ext.clone()), ..wopts.clone() };
+        prepare_output_dir(output, wopts.force)?;
+        for (i, (train, test)) in fold_pairs(&assigned, folds)?.iter().enumerate() {
+            let dir = Path::new(output).join(format!("fold_{i}"));
+            std::fs::create_dir_all(&dir)?;
+            write_df_with(train, &dir.join(format!("train.{ext}")).to_string_lossy(), &part_opts)?;
+            write_df_with(test, &dir.join(format!("test.{ext}")).to_string_lossy(), &part_opts)?;
+            println!("fold {i}: train {} rows, test {} rows", train.height(), test.height());
+        }
+    }
+    println!("seed: {seed}");
+    Ok(())
+}
+
+/// Adds a `fold` column (0..folds) dealing shuffled rows round-robin, class by class when
+/// stratifying, so fold sizes differ by at most one and each fold keeps the class mix.
+pub fn assign_folds(df: &DataFrame, folds: usize, stratify: Option<&str>, seed: u64) -> Result<DataFrame> {
+    if folds < 2 { bail!("--folds must be at least 2"); }
+    if folds > df.height() { bail!("--folds {folds} is more than the {} input rows", df.height()); }
+    let groups: Vec<Vec<usize>> = match stratify {
+        Some(c) => {
+            let keys = df.column(c)?.cast(&DataType::String)?;
+            let mut index: HashMap<Option<&str>, usize> = HashMap::new();
+            let mut groups: Vec<Vec<usize>> = vec![];
+            for (row, key) in keys.str()?.into_iter().enumerate() {
+                let g = *index.entry(key).or_insert_with(|| { groups.push(vec![]); groups.len() - 1 });
+                groups[g].push(row);
+            }
+            groups
+        }
+        None => vec![(0..df.height()).collect()],
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut fold = vec![0u32; df.height()];
+    let mut next = 0usize;
+    for mut rows in groups {
+        rows.shuffle(&mut rng);
+        for row in rows {
+            fold[row] = (next % folds) as u32;
+            next += 1;
+        }
+    }
+    let mut out = df.clone();
+    out.with_column(Series::new("fold".into(), fold))?;
+    Ok(out)
+}
+
+/// (train, test) for each fold of a frame produced by `assign_folds`.
+fn fold_pairs(assigned: &DataFrame, folds: usize) -> Result<Vec<(DataFrame, DataFrame)>> {
+    let fold = assigned.column("fold")?.u32()?.clone();
+    let data = assigned.drop("fold")?;
+    (0..folds as u32)
+        .map(|i| -> Result<(DataFrame, DataFrame)> {
+            let is_test = fold.equal(i);
+            Ok((data.filter(&!&is_test)?, data.filter(&is_test)?))
+        })
+        .collect()
+}
+
+/// Writes `<output_dir>/fold_<i>/{train,test}.parquet` and returns the pairs of paths.
+/// `fold_<i>` directories from an earlier run are replaced; nothing else in `output_dir` is touched.
+pub fn kfold_to_paths(input: &str, output_dir: &str, folds: usize, seed: Option<u64>, stratify: Option<&str>) -> Result<Vec<(String, String)>> {
+    let df = infer_reader(input)?.collect()?;
+    let assigned = assign_folds(&df, folds, stratify, seed.unwrap_or_else(random_seed))?;
+    if Path::new(output_dir).is_dir() {
+        for entry in std::fs::read_dir(output_dir)? {
+            let path = entry?.path();
+            if is_fold_dir(&path) { std::fs::remove_dir_all(&path)?; }
+        }
+    }
+    let mut paths = vec![];
+    for (i, (train, test)) in fold_pairs(&assigned, folds)?.iter().enumerate() {
+        let dir = Path::new(output_dir).join(format!("fold_{i}"));
+        std::fs::create_dir_all(&dir)?;
+        let (train_out, test_out) = (dir.join("train.parquet"), dir.join("test.parquet"));
+        crate::io::write_df(train, &train_out.to_string_lossy())?;
+        crate::io::write_df(test, &test_out.to_string_lossy())?;
+        paths.push((train_out.to_string_lossy().into_owned(), test_out.to_string_lossy().into_owned()));
+    }
+    Ok(paths)
+}
@@ -0,0 +1,74 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use polars::sql::{sql_expr, SQLContext};
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+
+pub fn sql_cmd(m: &ArgMatches) -> Result<()> {
+    let query = m.get_one::<String>("query").unwrap();
+    let output = m.get_one::<String>("output");
+    let ropts = ReadOptions::from_matches(m);
+
+    let mut ctx = SQLContext::new();
+    if let Some(tables) = m.get_many::<String>("table") {
+        for t in tables {
+            let Some((name, path)) = t.split_once('=') else {
+                bail!("Invalid --table {t:?}; expected name=path");
+            };
+            ctx.register(name.trim(), infer_reader_with(path.trim(), &ropts)?);
+        }
+    }
+
+    // Quoted paths after FROM/JOIN are registered as tables and swapped for
+    // generated table names, so queries can reference files directly.
+    let mut rewritten = String::with_capacity(query.len());
+    let mut rest = query.as_str();
+    let mut n = 0;
+    while let Some(start) = rest.find('\'') {
+        let Some(len) = rest[start + 1..].find('\'') else { break };
+        let (before, literal) = (&rest[..start], &rest[start + 1..start + 1 + len]);
+        rewritten.push_str(before);
+        if follows_table_keyword(&rewritten) {
+            let name = format!("__dpa_t{n}");
+            ctx.register(&name, infer_reader_with(literal, &ropts)?);
+            rewritten.push_str(&name);
+            n += 1;
+        } else {
+            rewritten.push('\'');
+            rewritten.push_str(literal);
+            rewritten.push('\'');
+        }
+        rest = &rest[start + len + 2..];
+    }
+    rewritten.push_str(rest);
+
+    let df = ctx.execute(&rewritten)?.collect()?;
+    match output {
+        Some(out) => write_df_with(&df, out, &WriteOptions::from_matches(m))?,
+        None => println!("{df}"),
+    }
+    Ok(())
+}
+
+fn follows_table_keyword(sql: &str) -> bool {
+    let last = sql.trim_end().rsplit(|c: char| c.is_whitespace() || c == '(' || c == ',').next().unwrap_or("");
+    last.eq_ignore_ascii_case("from") || last.eq_ignore_ascii_case("join")
+}
+
+pub fn derive_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    // Applied one at a time so later expressions can use columns derived earlier.
+    for spec in m.get_many::<String>("expr").unwrap() {
+        let (name, expr) = match spec.split_once('=') {
+            Some((name, expr)) if !name.trim().is_empty() && !expr.starts_with('=') => (name.trim(), expr.trim()),
+            _ => bail!("Invalid --expr {spec:?}; expected name = expression"),
+        };
+        lf = lf.with_column(sql_expr(expr)?.alias(name));
+    }
+    let df = lf.collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
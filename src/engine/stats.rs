@@ -0,0 +1,253 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use std::collections::HashMap;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::parse_names;
+
+const DESCRIBE_STATS: [&str; 12] = [
+    "count", "null_count", "mean", "std", "min", "25%", "50%", "75%", "max", "unique", "top", "freq",
+];
+
+pub fn describe_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let output = m.get_one::<String>("output");
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.collect()?;
+    let mut columns = vec![Series::new("statistic".into(), DESCRIBE_STATS)];
+    for s in df.get_columns() {
+        let vals = if s.dtype().is_numeric() { describe_numeric(s)? } else { describe_other(s)? };
+        columns.push(Series::new(s.name().clone(), vals));
+    }
+    let out = DataFrame::new(columns)?;
+    match output {
+        Some(out_path) => write_df_with(&out, out_path, &WriteOptions::from_matches(m))?,
+        None => println!("{out}"),
+    }
+    Ok(())
+}
+
+pub fn count_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let by = m.get_one::<String>("by");
+    let output = m.get_one::<String>("output");
+    let ropts = ReadOptions::from_matches(m);
+
+    let Some(by) = by else {
+        // Parquet footers carry the row count, so skip scanning entirely.
+        let is_parquet = matches!(ropts.format.as_deref(), Some("parquet" | "pq"))
+            || (ropts.format.is_none() && (input.ends_with(".parquet") || input.ends_with(".pq")));
+        let n = if is_parquet && ropts.dtypes.is_none() && ropts.schema_file.is_none() {
+            ParquetReader::new(std::fs::File::open(input)?).num_rows()?
+        } else {
+            let df = infer_reader_with(input, &ropts)?.select([len()]).collect()?;
+            df.get_columns()[0].u32()?.get(0).unwrap_or(0) as usize
+        };
+        println!("{n}");
+        return Ok(());
+    };
+
+    let keys: Vec<Expr> = parse_names(by).iter().map(|c| col(c.as_str())).collect();
+    let df = infer_reader_with(input, &ropts)?
+        .group_by(keys.clone())
+        .agg([len().alias("count")])
+        .sort_by_exprs(keys, SortMultipleOptions::default())
+        .collect()?;
+    match output {
+        Some(out) => write_df_with(&df, out, &WriteOptions::from_matches(m))?,
+        None => println!("{df}"),
+    }
+    Ok(())
+}
+
+pub fn corr_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let method = m.get_one::<String>("method").unwrap();
+    let output = m.get_one::<String>("output");
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let columns = match m.get_one::<String>("columns") {
+        Some(c) => parse_names(c),
+        None => lf.clone().collect_schema()?.iter()
+            .filter(|(_, dt)| dt.is_numeric())
+            .map(|(name, _)| name.to_string())
+            .collect(),
+    };
+    if columns.len() < 2 { bail!("Need at least two numeric columns to correlate"); }
+
+    // Spearman is Pearson over average ranks.
+    let x = |c: &str| -> Result<Expr> {
+        let e = col(c).cast(DataType::Float64);
+        Ok(match method.as_str() {
+            "pearson" => e,
+            "spearman" => e.rank(RankOptions { method: RankMethod::Average, descending: false }, None)
+                .cast(DataType::Float64),
+            other => bail!("Unsupported method={}. Use pearson or spearman.", other),
+        })
+    };
+    // One select computes the upper triangle; the matrix mirrors it.
+    let mut pairs = vec![];
+    for i in 0..columns.len() {
+        for j in i + 1..columns.len() {
+            pairs.push(pearson_corr(x(&columns[i])?, x(&columns[j])?).alias(&format!("{i}_{j}")));
+        }
+    }
+    let row = lf.select(pairs).collect()?;
+    let get = |i: usize, j: usize| -> Result<Option<f64>> {
+        if i == j { return Ok(Some(1.0)); }
+        let (i, j) = (i.min(j), i.max(j));
+        Ok(row.column(&format!("{i}_{j}"))?.f64()?.get(0))
+    };
+
+    let mut out = vec![Series::new("column".into(), &columns)];
+    for (j, name) in columns.iter().enumerate() {
+        let vals = (0..columns.len()).map(|i| get(i, j)).collect::<Result<Vec<_>>>()?;
+        out.push(Series::new(name.as_str().into(), vals));
+    }
+    let out = DataFrame::new(out)?;
+    match output {
+        Some(out_path) => write_df_with(&out, out_path, &WriteOptions::from_matches(m))?,
+        None => println!("{out}"),
+    }
+    Ok(())
+}
+
+pub fn value_counts_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let top = m.get_one::<usize>("top").copied();
+    let normalize = m.get_flag("normalize");
+    let output = m.get_one::<String>("output");
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let mut tables = vec![];
+    for c in &columns {
+        // Most frequent first, ties broken by value so output is stable.
+        let mut t = lf.clone()
+            .group_by([col(c.as_str())])
+            .agg([len().alias("count")])
+            .sort(["count", c.as_str()], SortMultipleOptions::default().with_order_descending_multi([true, false]));
+        if normalize {
+            // Share of all rows, computed before --top trims the table.
+            t = t.with_column((col("count").cast(DataType::Float64) / col("count").sum()).alias("proportion"));
+        }
+        if let Some(n) = top {
+            t = t.limit(n as IdxSize);
+        }
+        tables.push(t);
+    }
+
+    // A single column keeps its own name; several are stacked as column/value rows.
+    let df = if let [t] = tables.as_slice() {
+        t.clone().collect()?
+    } else {
+        let long = tables.into_iter().zip(&columns)
+            .map(|(t, c)| t.select([
+                lit(c.as_str()).alias("column"),
+                col(c.as_str()).cast(DataType::String).alias("value"),
+                col("*").exclude([c.as_str()]),
+            ]))
+            .collect::<Vec<_>>();
+        concat(long, UnionArgs::default())?.collect()?
+    };
+    match output {
+        Some(out) => write_df_with(&df, out, &WriteOptions::from_matches(m))?,
+        None => println!("{df}"),
+    }
+    Ok(())
+}
+
+fn fmt_num(v: Option<f64>) -> Option<String> {
+    v.map(|v| format!("{v:.4}"))
+}
+
+fn describe_numeric(s: &Series) -> Result<Vec<Option<String>>> {
+    let cast = s.cast(&DataType::Float64)?;
+    let ca = cast.f64()?;
+    let q = |p: f64| -> Result<Option<String>> {
+        Ok(fmt_num(ca.quantile(p, QuantileInterpolOptions::Linear)?))
+    };
+    Ok(vec![
+        Some((ca.len() - ca.null_count()).to_string()),
+        Some(ca.null_count().to_string()),
+        fmt_num(ca.mean()),
+        fmt_num(ca.std(1)),
+        fmt_num(ca.min()),
+        q(0.25)?,
+        q(0.5)?,
+        q(0.75)?,
+        fmt_num(ca.max()),
+        None,
+        None,
+        None,
+    ])
+}
+
+fn describe_other(s: &Series) -> Result<Vec<Option<String>>> {
+    let cast = s.cast(&DataType::String)?;
+    let mut freq: HashMap<&str, usize> = HashMap::new();
+    for v in cast.str()?.into_iter().flatten() {
+        *freq.entry(v).or_default() += 1;
+    }
+    // ties resolve to the lexicographically smallest value for stable output
+    let top = freq.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+    Ok(vec![
+        Some((s.len() - s.null_count()).to_string()),
+        Some(s.null_count().to_string()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(freq.len().to_string()),
+        top.map(|(v, _)| v.to_string()),
+        top.map(|(_, n)| n.to_string()),
+    ])
+}
+
+pub fn top_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let by = m.get_one::<String>("by").unwrap();
+    let k = *m.get_one::<usize>("k").unwrap();
+    let per = m.get_one::<String>("per").map(|s| parse_names(s)).unwrap_or_default();
+    let smallest = m.get_flag("smallest");
+    let output = m.get_one::<String>("output");
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = if per.is_empty() {
+        let by_expr = [col(by.as_str())];
+        let opts = SortMultipleOptions::default().with_nulls_last(true);
+        let lf = if smallest { lf.bottom_k(k as IdxSize, by_expr, opts) } else { lf.top_k(k as IdxSize, by_expr, opts) };
+        lf.collect()?
+    } else {
+        // Each group keeps its k rows as lists, which are exploded back into rows;
+        // only the selected rows get sorted for display.
+        let names: Vec<String> = lf.collect_schema()?.iter_names().map(|n| n.to_string()).collect();
+        let values: Vec<Expr> = names.iter()
+            .filter(|n| !per.contains(n))
+            .map(|n| col(n.as_str()))
+            .collect();
+        let pick = |e: Expr| if smallest {
+            e.bottom_k_by(lit(k as IdxSize), [col(by.as_str())], vec![false])
+        } else {
+            e.top_k_by(lit(k as IdxSize), [col(by.as_str())], vec![false])
+        };
+        let mut order = per.clone();
+        order.push(by.clone());
+        let mut descending = vec![false; per.len()];
+        descending.push(!smallest);
+        lf.group_by(per.iter().map(|c| col(c.as_str())).collect::<Vec<_>>())
+            .agg(values.iter().cloned().map(pick).collect::<Vec<_>>())
+            .explode(values)
+            .select(names.iter().map(|n| col(n.as_str())).collect::<Vec<_>>())
+            .sort(order, SortMultipleOptions::default().with_order_descending_multi(descending))
+            .collect()?
+    };
+    match output {
+        Some(out) => write_df_with(&df, out, &WriteOptions::from_matches(m))?,
+        None => println!("{df}"),
+    }
+    Ok(())
+}
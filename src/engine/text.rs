@@ -0,0 +1,229 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use crate::io::{parse_dtype, write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::{parse_names, parse_pairs};
+
+pub fn mask_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let method = m.get_one::<String>("method").unwrap();
+    let salt = m.get_one::<String>("salt").cloned().unwrap_or_default();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let exprs = columns.iter()
+        .map(|c| Ok(mask_expr(col(c.as_str()).cast(DataType::String), method, &salt)?.alias(c.as_str())))
+        .collect::<Result<Vec<_>>>()?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+// Nulls stay null under every method so masked files keep their missingness.
+fn mask_expr(x: Expr, method: &str, salt: &str) -> Result<Expr> {
+    Ok(match method {
+        "sha256" => {
+            let salt = salt.to_string();
+            x.map(move |s| {
+                let hashed: StringChunked = s.str()?.into_iter()
+                    .map(|v| v.map(|v| sha256_hex(&salt, v)))
+                    .collect();
+                Ok(Some(hashed.with_name(s.name().clone()).into_series()))
+            }, GetOutput::from_type(DataType::String))
+        }
+        "redact" => when(x.is_null()).then(lit(NULL).cast(DataType::String)).otherwise(lit("[REDACTED]")),
+        "last4" => concat_str([lit("****"), x.str().slice(lit(-4), lit(NULL))], "", false),
+        other => bail!("Unsupported method={}. Use sha256, redact or last4.", other),
+    })
+}
+
+fn sha256_hex(salt: &str, value: &str) -> String {
+    let digest = Sha256::new().chain_update(salt).chain_update(value).finalize();
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn extract_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let column = m.get_one::<String>("column").unwrap();
+    let pattern = m.get_one::<String>("pattern").unwrap();
+    let into = m.get_one::<String>("into").map(|s| parse_names(s))
+        .unwrap_or_else(|| vec![format!("{column}_extract")]);
+    let output = m.get_one::<String>("output").unwrap();
+
+    // The n-th --into name receives capture group n; rows that don't match get nulls.
+    let exprs = into.iter().enumerate()
+        .map(|(i, name)| {
+            col(column.as_str()).cast(DataType::String).str()
+                .extract(lit(pattern.as_str()), i + 1)
+                .alias(name.as_str())
+        })
+        .collect::<Vec<_>>();
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn split_col_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let column = m.get_one::<String>("column").unwrap();
+    let by = m.get_one::<String>("by").unwrap();
+    let into = parse_names(m.get_one::<String>("into").unwrap());
+    let types = m.get_one::<String>("types").map(|s| parse_pairs(s)).transpose()?.unwrap_or_default();
+    let drop = m.get_flag("drop");
+    let output = m.get_one::<String>("output").unwrap();
+
+    if into.is_empty() { bail!("--into needs at least one column name"); }
+    for (name, _) in &types {
+        if !into.contains(name) { bail!("--types refers to {name}, which is not in --into"); }
+    }
+
+    // splitn keeps any remainder in the last part, so "a b c" into first,last gives "a" and "b c".
+    let parts = col(column.as_str()).cast(DataType::String).str()
+        .splitn(lit(by.as_str()), into.len());
+    let mut exprs = vec![];
+    for (i, name) in into.iter().enumerate() {
+        let mut e = parts.clone().struct_().field_by_index(i as i64);
+        if let Some((_, ty)) = types.iter().find(|(n, _)| n == name) {
+            e = e.cast(parse_dtype(ty)?);
+        }
+        exprs.push(e.alias(name.as_str()));
+    }
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?.with_columns(exprs);
+    if drop && !into.contains(column) {
+        lf = lf.drop([column.as_str()]);
+    }
+    let df = lf.collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn concat_col_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let sep = m.get_one::<String>("sep").unwrap();
+    let into = m.get_one::<String>("into").unwrap();
+    let skip_nulls = m.get_flag("skip-nulls");
+    let output = m.get_one::<String>("output").unwrap();
+
+    if columns.is_empty() { bail!("--columns needs at least one column"); }
+    let parts = columns.iter().map(|c| col(c.as_str()).cast(DataType::String)).collect::<Vec<_>>();
+    let joined = concat_str(parts, sep, skip_nulls).alias(into.as_str());
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_column(joined).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn clean_text_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("columns").unwrap());
+    let ops = parse_names(m.get_one::<String>("ops").unwrap());
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut exprs = vec![];
+    for c in &columns {
+        // Ops apply in the order given.
+        let mut e = col(c.as_str()).cast(DataType::String);
+        for op in &ops {
+            e = match op.as_str() {
+                "trim" => e.str().strip_chars(lit(NULL)),
+                "lower" => e.str().to_lowercase(),
+                "upper" => e.str().to_uppercase(),
+                "collapse-spaces" => e.str().replace_all(lit(r"\s+"), lit(" "), false),
+                "strip-punct" => e.str().replace_all(lit(r"[\p{P}\p{S}]"), lit(""), false),
+                "strip-accents" => map_str(e, strip_accents),
+                other => bail!("Unsupported op: {other}. Use trim, lower, upper, collapse-spaces, strip-punct or strip-accents."),
+            };
+        }
+        exprs.push(e.alias(c.as_str()));
+    }
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn replace_re_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let pattern = m.get_one::<String>("pattern").unwrap();
+    let with = m.get_one::<String>("with").unwrap();
+    let first = m.get_flag("first");
+    let output = m.get_one::<String>("output").unwrap();
+
+    // The replacement may refer to capture groups as $1, $2, ...
+    let exprs = columns.iter().map(|c| {
+        let s = col(c.as_str()).cast(DataType::String).str();
+        let e = if first {
+            s.replace(lit(pattern.as_str()), lit(with.as_str()), false)
+        } else {
+            s.replace_all(lit(pattern.as_str()), lit(with.as_str()), false)
+        };
+        e.alias(c.as_str())
+    }).collect::<Vec<_>>();
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn tokens_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let column = m.get_one::<String>("column").unwrap();
+    let ngrams = parse_names(m.get_one::<String>("ngram").unwrap()).iter()
+        .map(|n| match n.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => bail!("Invalid n-gram size: {n:?}"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let top = m.get_one::<usize>("top").copied();
+    let output = m.get_one::<String>("output");
+
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?
+        .select([col(column.as_str()).cast(DataType::String)])
+        .collect()?;
+    // Tokens are lowercased alphanumeric runs; n-grams never span two values.
+    let mut counts: HashMap<(usize, String), u32> = HashMap::new();
+    for text in df.get_columns()[0].str()?.into_iter().flatten() {
+        let lower = text.to_lowercase();
+        let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+        for &n in &ngrams {
+            for gram in words.windows(n) {
+                *counts.entry((n, gram.join(" "))).or_default() += 1;
+            }
+        }
+    }
+    let mut rows: Vec<_> = counts.into_iter().collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if let Some(k) = top { rows.truncate(k); }
+
+    let out = DataFrame::new(vec![
+        Series::new("token".into(), rows.iter().map(|((_, t), _)| t.as_str()).collect::<Vec<_>>()),
+        Series::new("n".into(), rows.iter().map(|((n, _), _)| *n as u32).collect::<Vec<_>>()),
+        Series::new("count".into(), rows.iter().map(|(_, c)| *c).collect::<Vec<_>>()),
+    ])?;
+    match output {
+        Some(out_path) => write_df_with(&out, out_path, &WriteOptions::from_matches(m))?,
+        None => println!("{out}"),
+    }
+    Ok(())
+}
+
+// Applies a per-value string function, keeping nulls.
+fn map_str(e: Expr, f: fn(&str) -> String) -> Expr {
+    e.map(move |s| {
+        let out: StringChunked = s.str()?.into_iter().map(|v| v.map(f)).collect();
+        Ok(Some(out.with_name(s.name().clone()).into_series()))
+    }, GetOutput::from_type(DataType::String))
+}
+
+// Decompose (NFD) and drop the combining marks, so "Café" becomes "Cafe".
+fn strip_accents(v: &str) -> String {
+    v.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
@@ -0,0 +1,77 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use crate::io::{dtype_name, parse_dtype, infer_reader, infer_reader_with, ReadOptions};
+use super::parse_names;
+
+pub fn validate_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let schema = m.get_one::<String>("schema");
+    let not_null = m.get_one::<String>("not-null").map(|s| parse_names(s)).unwrap_or_default();
+    let unique = m.get_one::<String>("unique").map(|s| parse_names(s)).unwrap_or_default();
+
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let failures = run_checks(lf, schema.map(String::as_str), &not_null, &unique)?;
+    if failures.is_empty() {
+        println!("OK: all checks passed");
+        return Ok(());
+    }
+    for f in &failures {
+        println!("FAIL: {f}");
+    }
+    bail!("{} check(s) failed", failures.len())
+}
+
+/// Returns one message per failed check; an empty list means the data is valid.
+fn run_checks(lf: LazyFrame, schema: Option<&str>, not_null: &[String], unique: &[String]) -> Result<Vec<String>> {
+    let actual = lf.clone().collect_schema()?;
+    let mut failures = vec![];
+
+    // The schema file uses the same {"column": "Type"} map as --schema-file.
+    if let Some(path) = schema {
+        let raw = std::fs::read_to_string(path)?;
+        let expected: BTreeMap<String, String> = serde_json::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("Invalid schema file {path}: {e}"))?;
+        for (name, ty) in &expected {
+            match actual.get(name.as_str()) {
+                None => failures.push(format!("missing column {name}")),
+                // Compared by name, so any Datetime unit/zone matches "Datetime".
+                Some(dt) if !same_dtype(dt, ty) => {
+                    failures.push(format!("column {name} is {}, expected {ty}", dtype_name(dt)))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut checks = vec![];
+    for c in not_null {
+        checks.push(col(c.as_str()).null_count().cast(DataType::UInt64).alias(&format!("nulls:{c}")));
+    }
+    for c in unique {
+        checks.push((len() - col(c.as_str()).n_unique()).cast(DataType::UInt64).alias(&format!("dupes:{c}")));
+    }
+    if !checks.is_empty() {
+        let row = lf.select(checks).collect()?;
+        for s in row.get_columns() {
+            let n = s.u64()?.get(0).unwrap_or(0);
+            if n == 0 { continue; }
+            match s.name().split_once(':') {
+                Some(("nulls", c)) => failures.push(format!("column {c} has {n} null value(s)")),
+                Some((_, c)) => failures.push(format!("column {c} has {n} duplicate value(s)")),
+                None => {}
+            }
+        }
+    }
+    Ok(failures)
+}
+
+fn same_dtype(actual: &DataType, expected: &str) -> bool {
+    let expected = parse_dtype(expected).map(|dt| dtype_name(&dt)).unwrap_or_else(|_| expected.to_string());
+    dtype_name(actual).eq_ignore_ascii_case(&expected)
+}
+
+pub fn validate_path(input: &str, schema: Option<&str>, not_null: &[String], unique: &[String]) -> Result<Vec<String>> {
+    run_checks(infer_reader(input)?, schema, not_null, unique)
+}
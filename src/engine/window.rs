@@ -0,0 +1,218 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use crate::io::{write_df_with, infer_reader_with, ReadOptions, WriteOptions};
+use super::{parse_agg_specs, parse_names};
+
+pub fn window_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let over = m.get_one::<String>("over").map(|s| parse_names(s)).unwrap_or_default();
+    let order_by = m.get_one::<String>("order-by");
+    let rolling = m.get_one::<usize>("rolling").copied();
+    let aggs = parse_agg_specs(m.get_one::<String>("agg").unwrap())?;
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if let Some(order) = order_by {
+        lf = lf.sort(parse_names(order), SortMultipleOptions::default());
+    }
+
+    let partition: Vec<Expr> = over.iter().map(|c| col(c.as_str())).collect();
+    let mut exprs = vec![];
+    for (agg, c) in &aggs {
+        let expr = match rolling {
+            Some(n) => rolling_agg(col(c.as_str()), agg, n)?.alias(&format!("rolling_{agg}_{c}")),
+            None => super::agg_expr(col(c.as_str()), agg)?.alias(&format!("{agg}_{c}")),
+        };
+        exprs.push(if partition.is_empty() { expr } else { expr.over(partition.clone()) });
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn cum_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let over = m.get_one::<String>("over").map(|s| parse_names(s)).unwrap_or_default();
+    let order_by = m.get_one::<String>("order-by");
+    let ops = m.get_one::<String>("ops").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if let Some(order) = order_by {
+        lf = lf.sort(parse_names(order), SortMultipleOptions::default());
+    }
+
+    let partition: Vec<Expr> = over.iter().map(|c| col(c.as_str())).collect();
+    let mut exprs = vec![];
+    for op in parse_names(ops) {
+        // cumcount is the 1-based row number within the partition and takes no column.
+        let expr = match op.split_once(':') {
+            None if op == "cumcount" => int_range(lit(1), len() + lit(1), 1, DataType::UInt32).alias("cumcount"),
+            Some((op, c)) => {
+                let e = col(c);
+                let e = match op {
+                    "cumsum" => e.cum_sum(false),
+                    "cummin" => e.cum_min(false),
+                    "cummax" => e.cum_max(false),
+                    "cumprod" => e.cum_prod(false),
+                    other => bail!("Unsupported cumulative op: {other}"),
+                };
+                e.alias(&format!("{op}_{c}"))
+            }
+            None => bail!("Invalid op {op:?}; expected cumsum:column or cumcount"),
+        };
+        exprs.push(if partition.is_empty() { expr } else { expr.over(partition.clone()) });
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn rank_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let by = m.get_one::<String>("by").unwrap();
+    let per = m.get_one::<String>("per").map(|s| parse_names(s)).unwrap_or_default();
+    let descending = m.get_flag("descending");
+    let into = m.get_one::<String>("into").cloned().unwrap_or_else(|| format!("{by}_rank"));
+    let output = m.get_one::<String>("output").unwrap();
+
+    let method = match m.get_one::<String>("method").unwrap().as_str() {
+        "dense" => RankMethod::Dense,
+        "min" => RankMethod::Min,
+        "max" => RankMethod::Max,
+        "average" => RankMethod::Average,
+        "ordinal" => RankMethod::Ordinal,
+        other => bail!("Unsupported method={}. Use dense, min, max, average or ordinal.", other),
+    };
+    let mut rank = col(by.as_str()).rank(RankOptions { method, descending }, None);
+    if !per.is_empty() {
+        rank = rank.over(per.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+    }
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let df = lf.with_column(rank.alias(into.as_str())).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn pct_change_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let order_by = m.get_one::<String>("order-by");
+    let per = m.get_one::<String>("per").map(|s| parse_names(s)).unwrap_or_default();
+    let periods = *m.get_one::<i64>("periods").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if let Some(order) = order_by {
+        lf = lf.sort(parse_names(order), SortMultipleOptions::default());
+    }
+    // Changes are fractions of the earlier value: 0.1 means +10%.
+    let partition: Vec<Expr> = per.iter().map(|c| col(c.as_str())).collect();
+    let exprs = columns.iter().map(|c| {
+        let x = col(c.as_str()).cast(DataType::Float64);
+        let change = x.clone() / x.shift(lit(periods)) - lit(1.0);
+        let change = if partition.is_empty() { change } else { change.over(partition.clone()) };
+        change.alias(&format!("{c}_pct_change"))
+    }).collect::<Vec<_>>();
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn lag_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let columns = parse_names(m.get_one::<String>("column").unwrap());
+    let periods = parse_names(m.get_one::<String>("periods").unwrap()).iter()
+        .map(|p| p.parse::<i64>().map_err(|_| anyhow::anyhow!("Invalid period: {p:?}")))
+        .collect::<Result<Vec<_>>>()?;
+    let kinds = parse_names(m.get_one::<String>("kinds").unwrap());
+    let over = m.get_one::<String>("over").map(|s| parse_names(s)).unwrap_or_default();
+    let order_by = m.get_one::<String>("order-by");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    if let Some(order) = order_by {
+        lf = lf.sort(parse_names(order), SortMultipleOptions::default());
+    }
+
+    let partition: Vec<Expr> = over.iter().map(|c| col(c.as_str())).collect();
+    let mut exprs = vec![];
+    for c in &columns {
+        for &n in &periods {
+            for kind in &kinds {
+                let x = col(c.as_str());
+                // diff is the current value minus the value n rows earlier.
+                let e = match kind.as_str() {
+                    "lag" => x.shift(lit(n)),
+                    "lead" => x.shift(lit(-n)),
+                    "diff" => x.clone() - x.shift(lit(n)),
+                    other => bail!("Unsupported kind={}. Use lag, lead or diff.", other),
+                };
+                let e = if partition.is_empty() { e } else { e.over(partition.clone()) };
+                exprs.push(e.alias(&format!("{c}_{kind}{n}")));
+            }
+        }
+    }
+    let df = lf.with_columns(exprs).collect()?;
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+pub fn resample_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let time = m.get_one::<String>("time").unwrap();
+    let every = m.get_one::<String>("every").unwrap();
+    let aggs = parse_agg_specs(m.get_one::<String>("agg").unwrap())?;
+    let by = m.get_one::<String>("by").map(|s| parse_names(s)).unwrap_or_default();
+    let fill_gaps = m.get_flag("fill-gaps");
+    let output = m.get_one::<String>("output").unwrap();
+
+    let mut agg_exprs = vec![];
+    for (agg, c) in &aggs {
+        agg_exprs.push(match (agg.as_str(), c.as_str()) {
+            ("count", "*") => len().alias("count"),
+            _ => super::agg_expr(col(c.as_str()), agg)?.alias(&format!("{agg}_{c}")),
+        });
+    }
+
+    // group_by_dynamic needs the index sorted within each group.
+    let mut sort_cols = by.clone();
+    sort_cols.push(time.clone());
+    // Duration::parse panics on malformed input, so check the shape first.
+    let valid = regex::Regex::new(r"^(\d+(ns|us|ms|s|m|h|d|w|mo|q|y|i))+$").unwrap();
+    if !valid.is_match(every) {
+        bail!("Invalid --every interval {every:?}; expected e.g. 1h, 30m, 1d or 1d12h");
+    }
+    let every = Duration::parse(every);
+    let options = DynamicGroupOptions {
+        every,
+        period: every,
+        offset: Duration::parse("0ns"),
+        ..Default::default()
+    };
+    let lf = infer_reader_with(input, &ReadOptions::from_matches(m))?
+        .sort(sort_cols, SortMultipleOptions::default())
+        .group_by_dynamic(col(time.as_str()), by.iter().map(|c| col(c.as_str())).collect::<Vec<_>>(), options)
+        .agg(agg_exprs);
+    let mut df = lf.collect()?;
+    if fill_gaps {
+        // Upsampling inserts the empty intervals with null aggregates.
+        df = df.upsample(by, time, every)?;
+    }
+    write_df_with(&df, output, &WriteOptions::from_matches(m))?;
+    Ok(())
+}
+
+fn rolling_agg(e: Expr, agg: &str, n: usize) -> Result<Expr> {
+    let opts = RollingOptionsFixedWindow { window_size: n, min_periods: 1, ..Default::default() };
+    Ok(match agg {
+        "sum" => e.rolling_sum(opts),
+        "mean" => e.rolling_mean(opts),
+        "min" => e.rolling_min(opts),
+        "max" => e.rolling_max(opts),
+        "std" => e.rolling_std(opts),
+        "median" => e.rolling_median(opts),
+        other => bail!("Unsupported rolling aggregation: {other}"),
+    })
+}
@@ -0,0 +1,152 @@
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use std::io::Cursor;
+
+use super::ReadOptions;
+
+pub fn csv_reader(path: &str, opts: &ReadOptions, overrides: Option<SchemaRef>) -> Result<LazyFrame> {
+    let encoding = opts.encoding.as_deref().unwrap_or("utf8");
+    let on_bad_lines = opts.on_bad_lines.as_deref().unwrap_or("error");
+    if opts.bad_lines_output.is_some() && on_bad_lines == "error" {
+        bail!("--bad-lines-output requires --on-bad-lines skip or warn");
+    }
+    if let Some(out) = &opts.bad_lines_output {
+        if !opts.force && std::path::Path::new(out).exists() {
+            bail!("Output {out} already exists; pass --force to overwrite it");
+        }
+    }
+    if encoding == "utf8" && on_bad_lines == "error" {
+        let r = LazyCsvReader::new(path.to_string())
+            .with_has_header(!opts.no_header)
+            .with_skip_rows(opts.skip_rows)
+            .with_dtype_overwrite(overrides)
+            .map_parse_options(|p| parse_options(p, opts));
+        return Ok(r.finish()?);
+    }
+
+    // Non-UTF8 input is transcoded and malformed rows are dropped in memory
+    // before Polars parses the buffer.
+    let bytes = std::fs::read(path)?;
+    let mut text = decode(bytes, encoding)?;
+    if on_bad_lines != "error" {
+        text = drop_bad_lines(&text, opts, on_bad_lines == "warn")?;
+    }
+    let df = CsvReadOptions::default()
+        .with_has_header(!opts.no_header)
+        .with_skip_rows(opts.skip_rows)
+        .with_schema_overwrite(overrides)
+        .map_parse_options(|p| parse_options(p, opts))
+        .into_reader_with_file_handle(Cursor::new(text.into_bytes()))
+        .finish()?;
+    Ok(df.lazy())
+}
+
+fn parse_options(mut p: CsvParseOptions, opts: &ReadOptions) -> CsvParseOptions {
+    if let Some(d) = opts.delimiter { p = p.with_separator(d); }
+    if let Some(q) = opts.quote_char { p = p.with_quote_char(Some(q)); }
+    if !opts.null_values.is_empty() {
+        let tokens = opts.null_values.iter().map(|v| v.as_str().into()).collect();
+        p = p.with_null_values(Some(NullValues::AllColumns(tokens)));
+    }
+    p
+}
+
+fn drop_bad_lines(text: &str, opts: &ReadOptions, warn: bool) -> Result<String> {
+    let sep = opts.delimiter.unwrap_or(b',') as char;
+    let quote = opts.quote_char.unwrap_or(b'"') as char;
+
+    let mut kept = String::with_capacity(text.len());
+    let mut rejects = String::new();
+    let mut rejected = 0usize;
+    let mut expected: Option<usize> = None;
+    let mut line_no = 1usize;
+
+    for (i, record) in split_records(text, quote).into_iter().enumerate() {
+        let start_line = line_no;
+        line_no += record.matches('\n').count();
+        // Preamble lines skipped via --skip-rows and blank lines pass through.
+        if i < opts.skip_rows || record.trim_end_matches(['\r', '\n']).is_empty() {
+            kept.push_str(record);
+            continue;
+        }
+        let fields = count_fields(record, sep, quote);
+        // The header (or first row without one) fixes the expected width.
+        let want = *expected.get_or_insert(fields);
+        if fields == want {
+            kept.push_str(record);
+        } else {
+            rejected += 1;
+            if warn {
+                eprintln!("warning: skipping malformed line {start_line}: expected {want} fields, found {fields}");
+            }
+            rejects.push_str(record);
+            if !record.ends_with('\n') { rejects.push('\n'); }
+        }
+    }
+
+    if warn && rejected > 0 {
+        eprintln!("warning: skipped {rejected} malformed line(s)");
+    }
+    if let Some(out) = &opts.bad_lines_output {
+        std::fs::write(out, rejects)?;
+    }
+    Ok(kept)
+}
+
+// Splits on newlines that are not inside a quoted field, keeping terminators.
+fn split_records(text: &str, quote: char) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c == quote {
+            in_quotes = !in_quotes;
+        } else if c == '\n' && !in_quotes {
+            out.push(&text[start..=i]);
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+fn count_fields(record: &str, sep: char, quote: char) -> usize {
+    let mut in_quotes = false;
+    let mut n = 1;
+    for c in record.chars() {
+        if c == quote {
+            in_quotes = !in_quotes;
+        } else if c == sep && !in_quotes {
+            n += 1;
+        }
+    }
+    n
+}
+
+fn decode(bytes: Vec<u8>, encoding: &str) -> Result<String> {
+    let encoding = if encoding == "auto" { detect(&bytes) } else { encoding };
+    match encoding {
+        "utf8" => Ok(String::from_utf8(bytes)?),
+        // ISO-8859-1 maps every byte straight onto the matching code point.
+        "latin1" => Ok(bytes.iter().map(|&b| b as char).collect()),
+        "windows-1252" => {
+            let (text, _, _) = encoding_rs::WINDOWS_1252.decode(&bytes);
+            Ok(text.into_owned())
+        }
+        other => bail!("Unsupported encoding: {other}"),
+    }
+}
+
+// Valid UTF-8 wins; otherwise bytes in 0x80..=0x9F are printable in
+// windows-1252 but control codes in latin1, so they tip the balance.
+fn detect(bytes: &[u8]) -> &'static str {
+    if std::str::from_utf8(bytes).is_ok() {
+        "utf8"
+    } else if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) {
+        "windows-1252"
+    } else {
+        "latin1"
+    }
+}
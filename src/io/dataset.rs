@@ -0,0 +1,244 @@
+use anyhow::{Result, bail};
+use clap::ArgMatches;
+use polars::prelude::*;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use super::{infer_reader_with, write_df_with, ReadOptions, WriteOptions};
+
+pub fn compact_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let target = *m.get_one::<u64>("target-size").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+    let wopts = WriteOptions { format: Some("parquet".into()), ..WriteOptions::from_matches(m) };
+
+    let root = Path::new(input);
+    if !root.is_dir() { bail!("{input} is not a directory"); }
+    let out = Path::new(output);
+    if out.exists() && root.canonicalize()?.starts_with(out.canonicalize()?) {
+        bail!("Output {output} must not contain the input directory");
+    }
+    prepare_output_dir(output, wopts.force)?;
+
+    // Files are only merged with siblings, so key=value partition directories survive.
+    let mut by_dir: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for f in walk_files(root, &["parquet", "pq"])? {
+        let rel = f.parent().unwrap().strip_prefix(root)?.to_path_buf();
+        by_dir.entry(rel).or_default().push(f);
+    }
+
+    let (mut files_in, mut files_out) = (0, 0);
+    for (rel, files) in by_dir {
+        let out_dir = Path::new(output).join(&rel);
+        std::fs::create_dir_all(&out_dir)?;
+        // Batch siblings until their on-disk size reaches the target; compressed
+        // sizes add up closely enough to size the merged file.
+        let mut batches: Vec<Vec<PathBuf>> = vec![vec![]];
+        let mut batch_bytes = 0;
+        for f in files {
+            let bytes = std::fs::metadata(&f)?.len();
+            if batch_bytes > 0 && batch_bytes + bytes > target {
+                batches.push(vec![]);
+                batch_bytes = 0;
+            }
+            batch_bytes += bytes;
+            batches.last_mut().unwrap().push(f);
+        }
+        for (part, batch) in batches.iter().enumerate() {
+            let mut merged: Option<DataFrame> = None;
+            for f in batch {
+                let df = ParquetReader::new(std::fs::File::open(f)?).finish()?;
+                match merged.as_mut() {
+                    Some(acc) => {
+                        acc.vstack_mut(&df)
+                            .map_err(|e| anyhow::anyhow!("Schema of {} differs from its siblings: {e}", f.display()))?;
+                    }
+                    None => merged = Some(df),
+                }
+            }
+            let Some(mut merged) = merged else { continue };
+            merged.align_chunks();
+            let path = out_dir.join(part_name(part, "parquet"));
+            write_df_with(&merged, &path.to_string_lossy(), &wopts)?;
+            files_in += batch.len();
+            files_out += 1;
+        }
+    }
+    println!("Compacted {files_in} file(s) into {files_out}");
+    Ok(())
+}
+
+pub fn repartition_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let by: Vec<String> = m.get_one::<String>("by").unwrap().split(',').map(|c| c.trim().to_string()).collect();
+    let sort_within: Vec<String> = m.get_one::<String>("sort-within")
+        .map(|s| s.split(',').map(|c| c.trim().to_string()).collect())
+        .unwrap_or_default();
+    let partitions = m.get_one::<usize>("partitions").copied();
+    let output = m.get_one::<String>("output").unwrap();
+    let wopts = WriteOptions::from_matches(m);
+    let ext = wopts.format.clone().unwrap_or_else(|| "parquet".into());
+    let part_opts = WriteOptions { format: Some(ext.clone()), ..wopts.clone() };
+
+    let mut order = by.clone();
+    order.extend(sort_within.iter().cloned());
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?
+        .sort(order, SortMultipleOptions::default())
+        .collect()?;
+    prepare_output_dir(output, wopts.force)?;
+
+    match partitions {
+        // Contiguous key ranges keep each file's min/max statistics tight. Files are
+        // only cut between keys, so a key never spans two files; a dominant key can
+        // leave fewer than n.
+        Some(n) => {
+            let target = df.height().div_ceil(n.max(1)).max(1);
+            let runs = df.clone().lazy()
+                .group_by_stable(by.iter().map(|c| col(c.as_str())).collect::<Vec<_>>())
+                .agg([len().alias("__rows")])
+                .collect()?;
+            let runs = runs.column("__rows")?.cast(&DataType::UInt64)?;
+            let (mut part, mut start, mut end) = (0, 0, 0);
+            for rows in runs.u64()?.into_no_null_iter() {
+                end += rows as usize;
+                if end - start >= target {
+                    let path = Path::new(output).join(part_name(part, &ext));
+                    write_df_with(&df.slice(start as i64, end - start), &path.to_string_lossy(), &part_opts)?;
+                    (part, start) = (part + 1, end);
+                }
+            }
+            if end > start || part == 0 {
+                let path = Path::new(output).join(part_name(part, &ext));
+                write_df_with(&df.slice(start as i64, end - start), &path.to_string_lossy(), &part_opts)?;
+            }
+        }
+        // One key=value directory per key, hive style; the keys live in the path.
+        None => {
+            for group in df.partition_by_stable(by.clone(), true)? {
+                let mut dir = PathBuf::from(output);
+                for k in &by {
+                    let value = group.column(k)?.cast(&DataType::String)?;
+                    let value = value.str()?.get(0).unwrap_or("__null__").to_string();
+                    dir.push(format!("{}={}", escape_partition(k), escape_partition(&value)));
+                }
+                std::fs::create_dir_all(&dir)?;
+                let path = dir.join(part_name(0, &ext));
+                write_df_with(&group.drop_many(by.iter().map(String::as_str)), &path.to_string_lossy(), &part_opts)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn convert_dir_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let output = m.get_one::<String>("output").unwrap();
+    let from = m.get_one::<String>("from").unwrap();
+    let to = m.get_one::<String>("to").unwrap();
+    let jobs = m.get_one::<usize>("jobs").copied().unwrap_or(0);
+    let ropts = ReadOptions { format: Some(from.clone()), ..ReadOptions::from_matches(m) };
+    let wopts = WriteOptions { format: Some(to.clone()), ..WriteOptions::from_matches(m) };
+
+    let root = Path::new(input);
+    if !root.is_dir() { bail!("{input} is not a directory"); }
+    let files = walk_files(root, &[from.as_str()])?;
+    if files.is_empty() { bail!("No .{from} files found under {input}"); }
+
+    // The output tree mirrors the input tree with the extension swapped.
+    let convert = |f: &PathBuf| -> Result<()> {
+        let dest = Path::new(output).join(f.strip_prefix(root)?).with_extension(to);
+        std::fs::create_dir_all(dest.parent().unwrap())?;
+        let df = infer_reader_with(&f.to_string_lossy(), &ropts)?.collect()?;
+        write_df_with(&df, &dest.to_string_lossy(), &wopts)
+    };
+    // jobs=0 lets rayon pick one worker per core.
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    let failures: Vec<String> = pool.install(|| {
+        files.par_iter()
+            .filter_map(|f| convert(f).err().map(|e| format!("{}: {e}", f.display())))
+            .collect()
+    });
+    for f in &failures {
+        eprintln!("error: {f}");
+    }
+    println!("Converted {} of {} file(s)", files.len() - failures.len(), files.len());
+    if !failures.is_empty() { bail!("{} file(s) failed to convert", failures.len()); }
+    Ok(())
+}
+
+// Recursively lists files with one of the given extensions, in path order.
+pub(crate) fn walk_files(dir: &Path, exts: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut out = vec![];
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(d) = stack.pop() {
+        for entry in std::fs::read_dir(&d)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.extension().and_then(|e| e.to_str())
+                .is_some_and(|e| exts.iter().any(|x| x.eq_ignore_ascii_case(e))) {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+// Percent-encodes characters that would nest directories or break the key=value
+// form, the same escaping hive and polars' hive reader use.
+fn escape_partition(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_control() || "\"#%'*/:=?\\{[]^".contains(c) {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{b:02X}"));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// Every command that writes a directory of files names them this way, so parts
+// sort in write order and appends line up with earlier parts.
+pub(crate) fn part_name(part: usize, ext: &str) -> String {
+    format!("part-{part:05}.{ext}")
+}
+
+// Entries the dataset commands write: part files, hive key=value directories and
+// kfold's fold_N directories.
+fn is_owned_entry(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else { return false };
+    if path.is_dir() { name.contains('=') || is_fold_dir(path) } else { name.starts_with("part-") }
+}
+
+pub(crate) fn is_fold_dir(path: &Path) -> bool {
+    path.is_dir() && path.file_name().and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix("fold_"))
+        .is_some_and(|i| !i.is_empty() && i.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// With --force the entries a previous run wrote are removed first, so stale parts
+// never mix with the new ones. Anything else in the directory means the path is
+// probably wrong, so it is refused rather than deleted.
+pub(crate) fn prepare_output_dir(output: &str, force: bool) -> Result<()> {
+    let path = Path::new(output);
+    if path.is_file() { bail!("Output {output} is a file; expected a directory"); }
+    if path.is_dir() {
+        let entries = std::fs::read_dir(path)?.map(|e| Ok(e?.path())).collect::<Result<Vec<_>>>()?;
+        if !entries.is_empty() && !force {
+            bail!("Output {output} already exists; pass --force to overwrite it");
+        }
+        if let Some(other) = entries.iter().find(|e| !is_owned_entry(e)) {
+            bail!("Output {output} contains {}, which dpa did not write; refusing to overwrite it", other.display());
+        }
+        for e in entries {
+            if e.is_dir() { std::fs::remove_dir_all(&e)?; } else { std::fs::remove_file(&e)?; }
+        }
+    }
+    std::fs::create_dir_all(path)?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook};
+
+// Excel caps a worksheet at 1,048,576 rows, one of which is the header.
+const MAX_DATA_ROWS: usize = 1_048_575;
+
+pub fn write_xlsx(df: &DataFrame, output: &str, sheet_name: Option<&str>) -> Result<()> {
+    if df.height() > MAX_DATA_ROWS {
+        bail!("{} rows do not fit in a single Excel sheet (max {MAX_DATA_ROWS})", df.height());
+    }
+
+    let mut wb = Workbook::new();
+    let ws = wb.add_worksheet();
+    ws.set_name(sheet_name.unwrap_or("Sheet1"))?;
+
+    let header = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9E1F2))
+        .set_border_bottom(FormatBorder::Thin);
+
+    for (c, s) in df.get_columns().iter().enumerate() {
+        let c = c as u16;
+        ws.write_string_with_format(0, c, s.name().to_string(), &header)?;
+        if s.dtype().is_numeric() {
+            let vals = s.cast(&DataType::Float64)?;
+            for (r, v) in vals.f64()?.into_iter().enumerate() {
+                if let Some(v) = v { ws.write_number(r as u32 + 1, c, v)?; }
+            }
+        } else if s.dtype() == &DataType::Boolean {
+            for (r, v) in s.bool()?.into_iter().enumerate() {
+                if let Some(v) = v { ws.write_boolean(r as u32 + 1, c, v)?; }
+            }
+        } else {
+            let vals = s.cast(&DataType::String)?;
+            for (r, v) in vals.str()?.into_iter().enumerate() {
+                if let Some(v) = v { ws.write_string(r as u32 + 1, c, v)?; }
+            }
+        }
+    }
+
+    if df.width() > 0 {
+        ws.set_freeze_panes(1, 0)?;
+        ws.autofilter(0, 0, df.height() as u32, df.width() as u16 - 1)?;
+        ws.autofit();
+    }
+    wb.save(output)?;
+    Ok(())
+}
@@ -1,50 +1,468 @@
 use anyhow::{Result, bail};
 use clap::ArgMatches;
 use polars::prelude::*;
+use std::io::BufRead;
 use std::path::Path;
 
+mod csv;
+mod dataset;
+mod excel;
+mod xml;
+
+pub use dataset::{compact_cmd, convert_dir_cmd, repartition_cmd};
+pub(crate) use dataset::{is_fold_dir, part_name, prepare_output_dir};
+
+/// Input-side knobs shared by every command that reads a file.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    pub format: Option<String>,
+    // CSV only
+    pub delimiter: Option<u8>,
+    pub no_header: bool,
+    pub skip_rows: usize,
+    pub null_values: Vec<String>,
+    pub quote_char: Option<u8>,
+    pub encoding: Option<String>,
+    pub on_bad_lines: Option<String>,
+    pub bad_lines_output: Option<String>,
+    // overwrite an existing --bad-lines-output file
+    pub force: bool,
+    // dtype hints, applied at scan time for CSV and as casts otherwise
+    pub dtypes: Option<String>,
+    pub schema_file: Option<String>,
+    pub xml_record_tag: Option<String>,
+}
+
+impl ReadOptions {
+    pub fn from_matches(m: &ArgMatches) -> Self {
+        ReadOptions {
+            format: m.get_one::<String>("input-format").cloned(),
+            delimiter: m.get_one::<u8>("delimiter").copied(),
+            no_header: m.get_flag("no-header"),
+            skip_rows: m.get_one::<usize>("skip-rows").copied().unwrap_or(0),
+            null_values: m.get_one::<String>("null-values")
+                .map(|s| s.split(',').map(|v| v.to_string()).collect())
+                .unwrap_or_default(),
+            quote_char: m.get_one::<u8>("quote-char").copied(),
+            encoding: m.get_one::<String>("encoding").cloned(),
+            on_bad_lines: m.get_one::<String>("on-bad-lines").cloned(),
+            bad_lines_output: m.get_one::<String>("bad-lines-output").cloned(),
+            force: m.get_flag("force"),
+            dtypes: m.get_one::<String>("dtypes").cloned(),
+            schema_file: m.get_one::<String>("schema-file").cloned(),
+            xml_record_tag: m.get_one::<String>("xml-record-tag").cloned(),
+        }
+    }
+}
+
+// Explicit --input-format/--output-format win over the file extension.
+fn resolve_format(path: &str, explicit: Option<&str>) -> String {
+    match explicit {
+        Some(f) => f.to_ascii_lowercase(),
+        None => Path::new(path).extension().and_then(|s| s.to_str()).unwrap_or("").to_ascii_lowercase(),
+    }
+}
+
 pub fn infer_reader(path: &str) -> Result<LazyFrame> {
-    let p = Path::new(path);
-    let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_ascii_lowercase();
-    match ext.as_str() {
-        "parquet" | "pq" => Ok(LazyFrame::scan_parquet(path, Default::default())?),
-        "csv" => Ok(LazyCsvReader::new(path.to_string()).finish()?),
-        "json" | "jsonl" => Ok(LazyJsonLineReader::new(path).finish()?),
+    infer_reader_with(path, &ReadOptions::default())
+}
+
+pub fn infer_reader_with(path: &str, opts: &ReadOptions) -> Result<LazyFrame> {
+    let ext = resolve_format(path, opts.format.as_deref());
+    let overrides = dtype_overrides(opts)?;
+    let lf = match ext.as_str() {
+        "parquet" | "pq" => LazyFrame::scan_parquet(path, Default::default())?,
+        "csv" => return csv::csv_reader(path, opts, overrides),
+        "json" | "jsonl" => LazyJsonLineReader::new(path).finish()?,
+        "xml" => xml::xml_reader(path, opts.xml_record_tag.as_deref())?,
+        "" => bail!("Cannot infer input format for {path}; pass --input-format"),
         other => bail!("Unsupported input extension: {other}"),
+    };
+    Ok(match overrides {
+        Some(schema) => lf.with_columns(
+            schema.iter().map(|(name, dt)| col(name.as_str()).cast(dt.clone())).collect::<Vec<_>>(),
+        ),
+        None => lf,
+    })
+}
+
+/// Parses a user-facing dtype name (`Int64`, `f64`, `Utf8`, `date`, ...).
+pub fn parse_dtype(s: &str) -> Result<DataType> {
+    Ok(match s.trim().to_ascii_lowercase().as_str() {
+        "int8" | "i8" => DataType::Int8,
+        "int16" | "i16" => DataType::Int16,
+        "int32" | "i32" => DataType::Int32,
+        "int64" | "i64" | "int" => DataType::Int64,
+        "uint8" | "u8" => DataType::UInt8,
+        "uint16" | "u16" => DataType::UInt16,
+        "uint32" | "u32" => DataType::UInt32,
+        "uint64" | "u64" => DataType::UInt64,
+        "float32" | "f32" => DataType::Float32,
+        "float64" | "f64" | "float" => DataType::Float64,
+        "utf8" | "string" | "str" => DataType::String,
+        "boolean" | "bool" => DataType::Boolean,
+        "date" => DataType::Date,
+        "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+        other => bail!("Unsupported dtype: {other}"),
+    })
+}
+
+/// Inverse of [`parse_dtype`]; nested types fall back to their display form.
+pub fn dtype_name(dt: &DataType) -> String {
+    match dt {
+        DataType::Int8 => "Int8".into(),
+        DataType::Int16 => "Int16".into(),
+        DataType::Int32 => "Int32".into(),
+        DataType::Int64 => "Int64".into(),
+        DataType::UInt8 => "UInt8".into(),
+        DataType::UInt16 => "UInt16".into(),
+        DataType::UInt32 => "UInt32".into(),
+        DataType::UInt64 => "UInt64".into(),
+        DataType::Float32 => "Float32".into(),
+        DataType::Float64 => "Float64".into(),
+        DataType::String => "String".into(),
+        DataType::Boolean => "Boolean".into(),
+        DataType::Date => "Date".into(),
+        DataType::Datetime(_, _) => "Datetime".into(),
+        other => other.to_string(),
     }
 }
 
+// Merges --schema-file and --dtypes (the latter wins on conflicts).
+fn dtype_overrides(opts: &ReadOptions) -> Result<Option<SchemaRef>> {
+    let mut pairs: Vec<(String, String)> = vec![];
+    if let Some(path) = &opts.schema_file {
+        let raw = std::fs::read_to_string(path)?;
+        let map: std::collections::BTreeMap<String, String> = serde_json::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("Invalid schema file {path}: {e}"))?;
+        pairs.extend(map);
+    }
+    if let Some(spec) = &opts.dtypes {
+        for item in spec.split(',').filter(|s| !s.trim().is_empty()) {
+            let Some((name, dt)) = item.split_once('=') else {
+                bail!("Invalid --dtypes entry {item:?}; expected column=Type");
+            };
+            pairs.push((name.trim().to_string(), dt.trim().to_string()));
+        }
+    }
+    if pairs.is_empty() { return Ok(None); }
+
+    let mut schema = Schema::default();
+    for (name, dt) in pairs {
+        schema.with_column(name.as_str().into(), parse_dtype(&dt)?);
+    }
+    Ok(Some(Arc::new(schema)))
+}
+
 pub fn schema_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
-    let lf = infer_reader(input)?;
-    let df = lf.collect()?;
-    println!("{:?}", df.schema());
+    let format = m.get_one::<String>("format").unwrap();
+    let save = m.get_one::<String>("save");
+    let mut lf = infer_reader_with(input, &ReadOptions::from_matches(m))?;
+    let schema = lf.collect_schema()?;
+
+    // json/yaml use the same {"column": "Type"} map that --schema-file and
+    // validate --schema read, in column order.
+    let entries: Vec<(String, String)> = schema.iter()
+        .map(|(name, dt)| Ok((serde_json::to_string(name.as_str())?, serde_json::to_string(&dtype_name(dt))?)))
+        .collect::<Result<_>>()?;
+    let json = format!(
+        "{{\n{}\n}}\n",
+        entries.iter().map(|(k, v)| format!("  {k}: {v}")).collect::<Vec<_>>().join(",\n"),
+    );
+    let text = match format.as_str() {
+        "text" => format!("{schema:?}\n"),
+        "json" => json.clone(),
+        "yaml" => entries.iter().map(|(k, v)| format!("{k}: {v}\n")).collect(),
+        other => bail!("Unsupported format={}. Use text, json or yaml.", other),
+    };
+    print!("{text}");
+    if let Some(path) = save {
+        if !m.get_flag("force") && Path::new(path).exists() {
+            bail!("Output {path} already exists; pass --force to overwrite it");
+        }
+        // Saved schemas are always JSON so they can be loaded back.
+        std::fs::write(path, json)?;
+    }
     Ok(())
 }
 
 pub fn head_cmd(m: &ArgMatches) -> Result<()> {
     let input = m.get_one::<String>("input").unwrap();
     let n: usize = m.get_one::<String>("n").unwrap().parse().unwrap_or(10);
-    let df = infer_reader(input)?.fetch(n)?;
-    println!("{df}");
+    let df = infer_reader_with(input, &ReadOptions::from_matches(m))?.fetch(n)?;
+    print_preview(&df, m)
+}
+
+// Prints a preview in the --format requested; --max-col-width only trims the
+// human-readable formats, csv and json stay lossless for piping.
+fn print_preview(df: &DataFrame, m: &ArgMatches) -> Result<()> {
+    let width = m.get_one::<usize>("max-col-width").copied();
+    match m.get_one::<String>("format").map(String::as_str).unwrap_or("table") {
+        "table" => {
+            if let Some(w) = width {
+                std::env::set_var("POLARS_FMT_STR_LEN", w.to_string());
+            }
+            println!("{df}");
+        }
+        "csv" => CsvWriter::new(std::io::stdout()).finish(&mut df.clone())?,
+        "json" => {
+            JsonWriter::new(std::io::stdout()).with_json_format(JsonFormat::Json).finish(&mut df.clone())?;
+            println!();
+        }
+        "markdown" => print!("{}", to_markdown(df, width)?),
+        other => bail!("Unsupported format={}. Use table, csv, json or markdown.", other),
+    }
     Ok(())
 }
 
-// write by extension
+fn to_markdown(df: &DataFrame, width: Option<usize>) -> Result<String> {
+    let cell = |v: &str| {
+        let v = v.replace('|', "\\|").replace('\n', " ");
+        match width {
+            Some(w) if v.chars().count() > w => format!("{}…", v.chars().take(w.saturating_sub(1)).collect::<String>()),
+            _ => v,
+        }
+    };
+    let names: Vec<String> = df.get_column_names().iter().map(|c| cell(c.as_str())).collect();
+    let mut out = format!("| {} |\n|{}|\n", names.join(" | "), vec!["---"; names.len()].join("|"));
+    let cols = df.get_columns().iter()
+        .map(|s| s.cast(&DataType::String))
+        .collect::<PolarsResult<Vec<_>>>()?;
+    for i in 0..df.height() {
+        let row = cols.iter()
+            .map(|s| Ok(cell(s.str()?.get(i).unwrap_or(""))))
+            .collect::<Result<Vec<_>>>()?;
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    Ok(out)
+}
+
+pub fn tail_cmd(m: &ArgMatches) -> Result<()> {
+    let input = m.get_one::<String>("input").unwrap();
+    let n: usize = m.get_one::<String>("n").unwrap().parse().unwrap_or(10);
+    let ropts = ReadOptions::from_matches(m);
+    let lf = infer_reader_with(input, &ropts)?;
+    // Parquet footers know the row count, so only the last row groups are read;
+    // other formats have no row index and are read in full before taking the tail.
+    let df = if matches!(resolve_format(input, ropts.format.as_deref()).as_str(), "parquet" | "pq") {
+        let total = ParquetReader::new(std::fs::File::open(input)?).num_rows()?;
+        let start = total.saturating_sub(n);
+        lf.slice(start as i64, (total - start) as IdxSize).collect()?
+    } else {
+        lf.tail(n as IdxSize).collect()?
+    };
+    print_preview(&df, m)
+}
+
+/// Output-side knobs shared by every command that writes a file.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    pub format: Option<String>,
+    pub sheet_name: Option<String>,
+    // CSV only
+    pub delimiter: Option<u8>,
+    pub no_header: bool,
+    pub quote_style: Option<String>,
+    pub null_value: Option<String>,
+    // Parquet only
+    pub compression: Option<String>,
+    pub compression_level: Option<i32>,
+    pub row_group_size: Option<usize>,
+    pub no_statistics: bool,
+    pub append: bool,
+    pub force: bool,
+    // split into out/part-NNNN.<ext> files
+    pub max_rows_per_file: Option<usize>,
+    pub max_file_size: Option<u64>,
+}
+
+impl WriteOptions {
+    pub fn from_matches(m: &ArgMatches) -> Self {
+        WriteOptions {
+            format: m.get_one::<String>("output-format").cloned(),
+            sheet_name: m.get_one::<String>("sheet-name").cloned(),
+            delimiter: m.get_one::<u8>("output-delimiter").copied(),
+            no_header: m.get_flag("output-no-header"),
+            quote_style: m.get_one::<String>("output-quote-style").cloned(),
+            null_value: m.get_one::<String>("output-null").cloned(),
+            compression: m.get_one::<String>("compression").cloned(),
+            compression_level: m.get_one::<i32>("compression-level").copied(),
+            row_group_size: m.get_one::<usize>("row-group-size").copied(),
+            no_statistics: m.get_flag("no-statistics"),
+            // only some subcommands accept --append
+            append: m.try_get_one::<bool>("append").ok().flatten().copied().unwrap_or(false),
+            force: m.get_flag("force"),
+            max_rows_per_file: m.get_one::<usize>("max-rows-per-file").copied(),
+            max_file_size: m.get_one::<u64>("max-file-size").copied(),
+        }
+    }
+}
+
+// write by extension; the Python bindings default to a scratch output path
+// (dpa_out.parquet), so this entry point keeps overwriting.
 pub fn write_df(df: &DataFrame, output: &str) -> Result<()> {
-    let ext = std::path::Path::new(output).extension().and_then(|s| s.to_str()).unwrap_or("").to_ascii_lowercase();
+    write_df_with(df, output, &WriteOptions { force: true, ..Default::default() })
+}
+
+pub fn write_df_with(df: &DataFrame, output: &str, opts: &WriteOptions) -> Result<()> {
+    if opts.max_rows_per_file.is_some() || opts.max_file_size.is_some() {
+        return write_chunked(df, output, opts);
+    }
+    if opts.append && Path::new(output).is_dir() {
+        return append_to_dataset(df, output, opts);
+    }
+    let appending = opts.append && Path::new(output).exists();
+    if !appending && !opts.force && Path::new(output).exists() {
+        bail!("Output {output} already exists; pass --force to overwrite it");
+    }
+    let ext = resolve_format(output, opts.format.as_deref());
     match ext.as_str() {
         "parquet" | "pq" => {
-            let w = ParquetWriter::new(std::fs::File::create(output)?);
-            w.with_statistics(StatisticsOptions::default())
-                .with_compression(ParquetCompression::Zstd(None))
-                .finish(&mut df.clone())?;
+            let compression = match opts.compression.as_deref() {
+                None | Some("zstd") => ParquetCompression::Zstd(
+                    opts.compression_level.map(ZstdLevel::try_new).transpose()?,
+                ),
+                Some("snappy") => ParquetCompression::Snappy,
+                Some("lz4") => ParquetCompression::Lz4Raw,
+                Some("uncompressed") => ParquetCompression::Uncompressed,
+                Some(other) => bail!("Unsupported parquet compression: {other}"),
+            };
+            if opts.compression_level.is_some() && !matches!(compression, ParquetCompression::Zstd(_)) {
+                bail!("--compression-level only applies to zstd");
+            }
+            let statistics = if opts.no_statistics { StatisticsOptions::empty() } else { StatisticsOptions::default() };
+            // Parquet footers can't be extended in place, so appending rewrites
+            // the file with the new rows stacked after the existing ones. The
+            // rewrite goes to a sibling that only replaces the file once complete,
+            // so an interrupted append leaves the existing rows intact.
+            let mut df = if appending {
+                let mut existing = ParquetReader::new(std::fs::File::open(output)?).finish()?;
+                existing.vstack_mut(df)
+                    .map_err(|e| anyhow::anyhow!("Cannot append to {output}: {e}"))?;
+                existing
+            } else { df.clone() };
+            let target = if appending { format!("{output}.dpa-tmp") } else { output.to_string() };
+            let w = ParquetWriter::new(std::fs::File::create(&target)?);
+            w.with_statistics(statistics)
+                .with_compression(compression)
+                .with_row_group_size(opts.row_group_size)
+                .finish(&mut df)?;
+            if appending {
+                std::fs::rename(&target, output)?;
+            }
         }
         "csv" => {
-            let mut w = CsvWriter::new(std::fs::File::create(output)?);
+            let quote_style = match opts.quote_style.as_deref() {
+                None | Some("necessary") => QuoteStyle::Necessary,
+                Some("always") => QuoteStyle::Always,
+                Some("non-numeric") => QuoteStyle::NonNumeric,
+                Some("never") => QuoteStyle::Never,
+                Some(other) => bail!("Unsupported quote style: {other}"),
+            };
+            if appending && !opts.no_header {
+                check_csv_header(df, output, opts.delimiter.unwrap_or(b','), quote_style)?;
+            }
+            let mut w = CsvWriter::new(open_output(output, appending)?)
+                .include_header(!opts.no_header && !appending)
+                .with_separator(opts.delimiter.unwrap_or(b','))
+                .with_quote_style(quote_style)
+                .with_null_value(opts.null_value.clone().unwrap_or_default());
             w.finish(&mut df.clone())?;
         }
+        "jsonl" | "ndjson" => {
+            let mut w = JsonWriter::new(open_output(output, appending)?)
+                .with_json_format(JsonFormat::JsonLines);
+            w.finish(&mut df.clone())?;
+        }
+        "xlsx" if appending => bail!("--append is not supported for .xlsx outputs"),
+        "xlsx" => excel::write_xlsx(df, output, opts.sheet_name.as_deref())?,
+        "" => bail!("Cannot infer output format for {output}; pass --output-format"),
         other => bail!("Unsupported output extension: {other}"),
     }
     Ok(())
 }
+
+// Appended rows are matched to the existing columns by position, so the header
+// they would be written with must be exactly the one already in the file.
+fn check_csv_header(df: &DataFrame, output: &str, separator: u8, quote_style: QuoteStyle) -> Result<()> {
+    let mut existing = String::new();
+    std::io::BufReader::new(std::fs::File::open(output)?).read_line(&mut existing)?;
+    let existing = existing.trim_end_matches(['\r', '\n']);
+    if existing.is_empty() { return Ok(()); }
+    let mut header: Vec<u8> = vec![];
+    CsvWriter::new(&mut header)
+        .include_header(true)
+        .with_separator(separator)
+        .with_quote_style(quote_style)
+        .finish(&mut df.clear())?;
+    let header = String::from_utf8_lossy(&header);
+    if header.trim_end_matches(['\r', '\n']) != existing {
+        bail!("Cannot append to {output}: its header {existing:?} does not match the output columns {:?}", df.get_column_names());
+    }
+    Ok(())
+}
+
+fn open_output(output: &str, append: bool) -> Result<std::fs::File> {
+    if append {
+        Ok(std::fs::OpenOptions::new().append(true).open(output)?)
+    } else {
+        Ok(std::fs::File::create(output)?)
+    }
+}
+
+// Appending to a dataset directory adds the rows as a new part file.
+fn append_to_dataset(df: &DataFrame, dir: &str, opts: &WriteOptions) -> Result<()> {
+    let parts = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("parquet"))
+        .count();
+    let path = Path::new(dir).join(part_name(parts, "parquet"));
+    let opts = WriteOptions { format: Some("parquet".into()), append: false, ..opts.clone() };
+    write_df_with(df, &path.to_string_lossy(), &opts)
+}
+
+// Rows per part honour both limits; the size limit is estimated from the
+// in-memory frame size, so compressed Parquet parts usually come out smaller.
+fn write_chunked(df: &DataFrame, dir: &str, opts: &WriteOptions) -> Result<()> {
+    let mut rows = opts.max_rows_per_file.unwrap_or(usize::MAX);
+    if let Some(max_bytes) = opts.max_file_size {
+        let bytes_per_row = (df.estimated_size() / df.height().max(1)).max(1) as u64;
+        rows = rows.min((max_bytes / bytes_per_row) as usize);
+    }
+    let rows = rows.max(1);
+
+    let dir_path = Path::new(dir);
+    if dir_path.is_file() {
+        bail!("Output {dir} is a file; chunked output needs a directory path");
+    }
+    let ext = opts.format.clone().unwrap_or_else(|| "parquet".into());
+    // Appending numbers the new parts after the existing ones; otherwise parts
+    // from an earlier run are cleared so they never mix with this output.
+    let mut part = if opts.append && dir_path.is_dir() {
+        std::fs::read_dir(dir_path)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("part-"))
+            .count()
+    } else {
+        prepare_output_dir(dir, opts.force)?;
+        0
+    };
+    let part_opts = WriteOptions {
+        format: Some(ext.clone()),
+        max_rows_per_file: None,
+        max_file_size: None,
+        append: false,
+        ..opts.clone()
+    };
+    let mut offset = 0;
+    loop {
+        let chunk = df.slice(offset as i64, rows);
+        let path = dir_path.join(part_name(part, &ext));
+        write_df_with(&chunk, &path.to_string_lossy(), &part_opts)?;
+        offset += rows;
+        part += 1;
+        if offset >= df.height() { break; }
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use polars::prelude::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+type Fields = Vec<(String, String)>;
+
+// Flattens each record element into one row: attributes and child elements
+// become String columns (deeper nesting is concatenated into the child's
+// text). Without a record tag, the root element's children are the records.
+pub fn xml_reader(path: &str, record_tag: Option<&str>) -> Result<LazyFrame> {
+    let mut reader = Reader::from_file(path)?;
+    reader.config_mut().trim_text(true);
+
+    let mut columns: Vec<String> = vec![];
+    let mut rows: Vec<Fields> = vec![];
+    let mut record: Option<(usize, Fields)> = None;
+    let mut field: Option<(String, String)> = None;
+    let mut depth = 0usize;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => {
+                depth += 1;
+                match &record {
+                    Some((rd, _)) if depth == rd + 1 => field = Some((local_name(&e), String::new())),
+                    Some(_) => {}
+                    None if is_record(&e, depth, record_tag) => record = Some((depth, attributes(&e)?)),
+                    None => {}
+                }
+            }
+            Event::Empty(e) => match &mut record {
+                Some((rd, values)) if depth == *rd => set(values, local_name(&e), String::new()),
+                Some(_) => {}
+                None if is_record(&e, depth + 1, record_tag) => push_row(attributes(&e)?, &mut columns, &mut rows),
+                None => {}
+            },
+            Event::Text(t) => {
+                if let Some((_, text)) = &mut field { text.push_str(&t.unescape()?); }
+            }
+            Event::CData(t) => {
+                if let Some((_, text)) = &mut field { text.push_str(&String::from_utf8_lossy(&t)); }
+            }
+            Event::End(_) => {
+                match &mut record {
+                    Some((rd, values)) if depth == *rd + 1 => {
+                        if let Some((name, text)) = field.take() { set(values, name, text); }
+                    }
+                    Some((rd, _)) if depth == *rd => {
+                        let (_, values) = record.take().unwrap();
+                        push_row(values, &mut columns, &mut rows);
+                    }
+                    _ => {}
+                }
+                depth = depth.saturating_sub(1);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let series = columns.iter()
+        .map(|c| {
+            let vals: Vec<Option<&str>> = rows.iter()
+                .map(|r| r.iter().find(|(k, _)| k == c).map(|(_, v)| v.as_str()))
+                .collect();
+            Series::new(c.as_str().into(), vals)
+        })
+        .collect::<Vec<_>>();
+    Ok(DataFrame::new(series)?.lazy())
+}
+
+fn is_record(e: &BytesStart, depth: usize, record_tag: Option<&str>) -> bool {
+    match record_tag {
+        Some(tag) => local_name(e) == tag,
+        None => depth == 2,
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+fn attributes(e: &BytesStart) -> Result<Fields> {
+    let mut out = vec![];
+    for a in e.attributes() {
+        let a = a?;
+        let key = String::from_utf8_lossy(a.key.local_name().as_ref()).into_owned();
+        set(&mut out, key, a.unescape_value()?.into_owned());
+    }
+    Ok(out)
+}
+
+// Repeated child elements keep the last value.
+fn set(values: &mut Fields, name: String, text: String) {
+    match values.iter_mut().find(|(k, _)| *k == name) {
+        Some((_, v)) => *v = text,
+        None => values.push((name, text)),
+    }
+}
+
+// Columns appear in the order they are first seen across records.
+fn push_row(values: Fields, columns: &mut Vec<String>, rows: &mut Vec<Fields>) {
+    for (k, _) in &values {
+        if !columns.contains(k) { columns.push(k.clone()); }
+    }
+    rows.push(values);
+}
@@ -36,11 +36,44 @@ fn profile_py(input: String) -> PyResult<Py<pyo3::types::PyDict>> {
     })
 }
 
+#[pyfunction]
+#[pyo3(signature = (input, size=None, seed=None, stratify=None, output=None, fraction=None))]
+fn sample_py(input: String, size: Option<usize>, seed: Option<u64>, stratify: Option<String>, output: Option<String>, fraction: Option<f64>) -> PyResult<String> {
+    engine::sample_to_path(&input, size, fraction, seed, stratify.as_deref(), output.as_deref())
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (input, train_output, test_output, test_size=0.2, seed=None, stratify=None))]
+fn split_py(input: String, train_output: String, test_output: String, test_size: f64, seed: Option<u64>, stratify: Option<String>) -> PyResult<(String, String)> {
+    engine::split_to_paths(&input, test_size, seed, stratify.as_deref(), &train_output, &test_output)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+    Ok((train_output, test_output))
+}
+
+#[pyfunction]
+#[pyo3(signature = (input, output_dir, folds=5, seed=None, stratify=None))]
+fn kfold_py(input: String, output_dir: String, folds: usize, seed: Option<u64>, stratify: Option<String>) -> PyResult<Vec<(String, String)>> {
+    engine::kfold_to_paths(&input, &output_dir, folds, seed, stratify.as_deref())
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (input, schema=None, not_null=None, unique=None))]
+fn validate_py(input: String, schema: Option<String>, not_null: Option<Vec<String>>, unique: Option<Vec<String>>) -> PyResult<Vec<String>> {
+    engine::validate_path(&input, schema.as_deref(), &not_null.unwrap_or_default(), &unique.unwrap_or_default())
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
 #[pymodule]
 fn dpa_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(filter_py, m)?)?;
     m.add_function(wrap_pyfunction!(select_py, m)?)?;
     m.add_function(wrap_pyfunction!(convert_py, m)?)?;
     m.add_function(wrap_pyfunction!(profile_py, m)?)?;
+    m.add_function(wrap_pyfunction!(sample_py, m)?)?;
+    m.add_function(wrap_pyfunction!(split_py, m)?)?;
+    m.add_function(wrap_pyfunction!(kfold_py, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_py, m)?)?;
     Ok(())
 }
@@ -11,12 +11,67 @@ fn main() -> Result<()> {
     match matches.subcommand() {
         Some(("schema", m)) => io::schema_cmd(m),
         Some(("head", m)) => io::head_cmd(m),
+        Some(("tail", m)) => io::tail_cmd(m),
         Some(("filter", m)) | Some(("f", m)) => engine::filter_cmd(m),
         Some(("select", m)) | Some(("s", m)) => engine::select_cmd(m),
         Some(("convert", m)) | Some(("c", m)) => engine::convert_cmd(m),
         Some(("profile", m)) | Some(("p", m)) => engine::profile_cmd(m),
         Some(("agg", m)) | Some(("a", m)) => engine::agg_cmd(m),
         Some(("join", m)) | Some(("j", m)) => engine::join_cmd(m),
+        Some(("dedup", m)) | Some(("distinct", m)) => engine::dedup_cmd(m),
+        Some(("sql", m)) => engine::sql_cmd(m),
+        Some(("pivot", m)) => engine::pivot_cmd(m),
+        Some(("melt", m)) | Some(("unpivot", m)) => engine::melt_cmd(m),
+        Some(("concat", m)) | Some(("union", m)) => engine::concat_cmd(m),
+        Some(("diff", m)) => engine::diff_cmd(m),
+        Some(("describe", m)) => engine::describe_cmd(m),
+        Some(("count", m)) => engine::count_cmd(m),
+        Some(("rename", m)) => engine::rename_cmd(m),
+        Some(("cast", m)) => engine::cast_cmd(m),
+        Some(("fillna", m)) => engine::fillna_cmd(m),
+        Some(("dropna", m)) => engine::dropna_cmd(m),
+        Some(("replace", m)) => engine::replace_cmd(m),
+        Some(("explode", m)) => engine::explode_cmd(m),
+        Some(("unnest", m)) => engine::unnest_cmd(m),
+        Some(("window", m)) => engine::window_cmd(m),
+        Some(("resample", m)) => engine::resample_cmd(m),
+        Some(("bin", m)) | Some(("cut", m)) => engine::bin_cmd(m),
+        Some(("encode", m)) => engine::encode_cmd(m),
+        Some(("scale", m)) | Some(("normalize", m)) => engine::scale_cmd(m),
+        Some(("mask", m)) | Some(("hash", m)) => engine::mask_cmd(m),
+        Some(("extract", m)) => engine::extract_cmd(m),
+        Some(("split-col", m)) => engine::split_col_cmd(m),
+        Some(("concat-col", m)) => engine::concat_col_cmd(m),
+        Some(("parse-dates", m)) => engine::parse_dates_cmd(m),
+        Some(("derive", m)) | Some(("with-column", m)) => engine::derive_cmd(m),
+        Some(("top", m)) => engine::top_cmd(m),
+        Some(("crosstab", m)) => engine::crosstab_cmd(m),
+        Some(("corr", m)) => engine::corr_cmd(m),
+        Some(("value-counts", m)) => engine::value_counts_cmd(m),
+        Some(("shuffle", m)) => engine::shuffle_cmd(m),
+        Some(("transpose", m)) => engine::transpose_cmd(m),
+        Some(("add-index", m)) => engine::add_index_cmd(m),
+        Some(("clip", m)) | Some(("winsorize", m)) => engine::clip_cmd(m),
+        Some(("cum", m)) => engine::cum_cmd(m),
+        Some(("rank", m)) => engine::rank_cmd(m),
+        Some(("pct-change", m)) => engine::pct_change_cmd(m),
+        Some(("interpolate", m)) => engine::interpolate_cmd(m),
+        Some(("round", m)) => engine::round_cmd(m),
+        Some(("lag", m)) => engine::lag_cmd(m),
+        Some(("tz", m)) => engine::tz_cmd(m),
+        Some(("clean-text", m)) => engine::clean_text_cmd(m),
+        Some(("replace-re", m)) => engine::replace_re_cmd(m),
+        Some(("tokens", m)) => engine::tokens_cmd(m),
+        Some(("compact", m)) => io::compact_cmd(m),
+        Some(("repartition", m)) => io::repartition_cmd(m),
+        Some(("convert-dir", m)) => io::convert_dir_cmd(m),
+        Some(("validate", m)) => engine::validate_cmd(m),
+        Some(("sample", m)) => engine::sample_cmd(m),
+        Some(("split", m)) => engine::split_cmd(m),
+        Some(("kfold", m)) => engine::kfold_cmd(m),
+        Some(("balance", m)) => engine::balance_cmd(m),
+        Some(("leak-check", m)) => engine::leak_check_cmd(m),
+        Some(("schema-diff", m)) => engine::schema_diff_cmd(m),
         _ => {
             println!("See --help for usage.");
             Ok(())
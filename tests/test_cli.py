@@ -1,6 +1,8 @@
 """
 Tests for the CLI functionality
 """
+import csv
+import json
 import pytest
 import subprocess
 import tempfile
@@ -60,6 +62,54 @@ class TestCLI:
         assert "user_id: Int64" in result.stdout
         assert "amount: Float64" in result.stdout
     
+    def test_profile_json(self, sample_data_path, temp_dir):
+        """Test profile writes a structured JSON report"""
+        output_path = os.path.join(temp_dir, "report.json")
+        result = subprocess.run([
+            "./target/debug/dpa", "profile", sample_data_path, "--format", "json", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            report = json.load(f)
+        assert report["rows_sampled"] == 500
+        columns = {c["name"]: c for c in report["columns"]}
+        assert list(columns) == ["user_id", "amount", "country", "timestamp", "channel"]
+        assert columns["amount"]["dtype"] == "Float64"
+        assert sum(b["count"] for b in columns["amount"]["numeric"]["histogram"]) == 500
+        assert columns["channel"]["top_values"][0]["count"] > 0
+    
+    def test_profile_html(self, sample_data_path, temp_dir):
+        """Test profile writes a self-contained HTML report"""
+        output_path = os.path.join(temp_dir, "report.html")
+        result = subprocess.run([
+            "./target/debug/dpa", "profile", sample_data_path, "--format", "html", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            html = f.read()
+        assert html.startswith("<!DOCTYPE html>")
+        assert "Quality summary" in html
+        assert 'id="col-1"' in html and 'href="#col-1"' in html
+        assert "<script" not in html and "http" not in html
+    
+    def test_describe_command(self, sample_data_path, temp_dir):
+        """Test describe command"""
+        output_path = os.path.join(temp_dir, "describe.csv")
+        result = subprocess.run(["./target/debug/dpa", "describe", sample_data_path, "-o", output_path], 
+                              capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            lines = f.read().splitlines()
+        assert lines[0] == "statistic,user_id,amount,country,timestamp,channel"
+        assert lines[1].startswith("count,500,500,500,500,500")
+    
+    def test_count_command(self, sample_data_path):
+        """Test count command"""
+        result = subprocess.run(["./target/debug/dpa", "count", sample_data_path], 
+                              capture_output=True, text=True)
+        assert result.returncode == 0
+        assert result.stdout.strip() == "500"
+    
     def test_convert_command(self, sample_data_path, temp_dir):
         """Test convert command"""
         output_path = os.path.join(temp_dir, "output.parquet")
@@ -69,6 +119,177 @@ class TestCLI:
         assert os.path.exists(output_path)
         assert os.path.getsize(output_path) > 0
     
+    def test_convert_to_jsonl(self, sample_data_path, temp_dir):
+        """Test convert command writing newline-delimited JSON"""
+        output_path = os.path.join(temp_dir, "output.jsonl")
+        result = subprocess.run(["./target/debug/dpa", "convert", sample_data_path, output_path], 
+                              capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            lines = f.read().splitlines()
+        assert len(lines) == 500
+        assert lines[0].startswith("{") and '"user_id"' in lines[0]
+    
+    def test_format_overrides(self, sample_data_path, temp_dir):
+        """Test --input-format/--output-format on extensionless paths"""
+        raw_path = os.path.join(temp_dir, "export.txt")
+        shutil.copy(sample_data_path, raw_path)
+        output_path = os.path.join(temp_dir, "out")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", raw_path, output_path,
+            "--input-format", "csv", "--output-format", "parquet"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path, "rb") as f:
+            assert f.read(4) == b"PAR1"
+    
+    def test_csv_read_options(self, temp_dir):
+        """Test reading a semicolon-separated CSV with custom null tokens"""
+        input_path = os.path.join(temp_dir, "eu.csv")
+        with open(input_path, "w") as f:
+            f.write("# exported 2024-01-01\nid;amount\n1;10,5\n2;NA\n3;-\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, output_path,
+            "--delimiter", ";", "--skip-rows", "1", "--null-values", "NA,-"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,amount", "1,\"10,5\"", "2,", "3,"]
+    
+    def test_latin1_encoding(self, temp_dir):
+        """Test reading a Latin-1 encoded CSV with encoding detection"""
+        input_path = os.path.join(temp_dir, "legacy.csv")
+        with open(input_path, "wb") as f:
+            f.write("city,n\nMünchen,1\nSão Paulo,2\n".encode("latin-1"))
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, output_path, "--encoding", "auto"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path, encoding="utf-8") as f:
+            assert "München" in f.read()
+    
+    def test_skip_bad_lines(self, temp_dir):
+        """Test skipping malformed CSV rows and collecting them"""
+        input_path = os.path.join(temp_dir, "dirty.csv")
+        with open(input_path, "w") as f:
+            f.write("id,name\n1,a\n2,b,extra\n3,\"c, d\"\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        rejects_path = os.path.join(temp_dir, "rejects.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, output_path,
+            "--on-bad-lines", "warn", "--bad-lines-output", rejects_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "line 3" in result.stderr
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,name", "1,a", "3,\"c, d\""]
+        with open(rejects_path) as f:
+            assert f.read() == "2,b,extra\n"
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, os.path.join(temp_dir, "again.csv"),
+            "--on-bad-lines", "warn", "--bad-lines-output", rejects_path
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "--force" in result.stderr
+    
+    def test_dtype_overrides(self, temp_dir):
+        """Test --dtypes keeps leading zeros by reading a column as a string"""
+        input_path = os.path.join(temp_dir, "zips.csv")
+        with open(input_path, "w") as f:
+            f.write("id,zip\n1,02134\n2,10001\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, output_path, "--dtypes", "zip=Utf8"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert "1,02134" in f.read()
+    
+    def test_append_csv(self, sample_data_path, temp_dir):
+        """Test --append adds rows to an existing CSV without repeating the header, and only if the columns match"""
+        output_path = os.path.join(temp_dir, "out.csv")
+        cmd = ["./target/debug/dpa", "filter", sample_data_path,
+               "-w", "amount > 100", "-o", output_path, "--append"]
+        assert subprocess.run(cmd, capture_output=True, text=True).returncode == 0
+        with open(output_path) as f:
+            first = f.read().splitlines()
+        assert subprocess.run(cmd, capture_output=True, text=True).returncode == 0
+        with open(output_path) as f:
+            second = f.read().splitlines()
+        assert second == first + first[1:]
+        
+        reordered = os.path.join(temp_dir, "reordered.csv")
+        with open(reordered, "w") as f:
+            f.write("amount,user_id,country,timestamp,channel\n1.0,1,US,2024-01-01,web\n")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", reordered, output_path, "--append"
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "does not match" in result.stderr
+        with open(output_path) as f:
+            assert f.read().splitlines() == second
+    
+    def test_refuses_to_overwrite(self, sample_data_path, temp_dir):
+        """Test existing outputs are only replaced with --force"""
+        output_path = os.path.join(temp_dir, "output.csv")
+        with open(output_path, "w") as f:
+            f.write("curated\n")
+        cmd = ["./target/debug/dpa", "convert", sample_data_path, output_path]
+        result = subprocess.run(cmd, capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "--force" in result.stderr
+        with open(output_path) as f:
+            assert f.read() == "curated\n"
+        result = subprocess.run(cmd + ["--force"], capture_output=True, text=True)
+        assert result.returncode == 0
+    
+    def test_chunked_output(self, sample_data_path, temp_dir):
+        """Test --max-rows-per-file writes numbered part files and --force replaces them"""
+        output_dir = os.path.join(temp_dir, "out")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", sample_data_path, output_dir,
+            "--max-rows-per-file", "200"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert sorted(os.listdir(output_dir)) == [
+            "part-00000.parquet", "part-00001.parquet", "part-00002.parquet"
+        ]
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", sample_data_path, output_dir,
+            "--max-rows-per-file", "300", "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert sorted(os.listdir(output_dir)) == ["part-00000.parquet", "part-00001.parquet"]
+    
+    def test_xml_input(self, temp_dir):
+        """Test flattening XML records into rows"""
+        input_path = os.path.join(temp_dir, "feed.xml")
+        with open(input_path, "w") as f:
+            f.write('<feed><meta>x</meta><entry id="1"><name>A &amp; B</name></entry>'
+                    '<entry id="2"><name>C</name><amount>5</amount></entry></feed>')
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", input_path, output_path, "--xml-record-tag", "entry"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,name,amount", "1,A & B,", "2,C,5"]
+    
+    def test_csv_write_options(self, sample_data_path, temp_dir):
+        """Test pipe-delimited CSV output without a header"""
+        output_path = os.path.join(temp_dir, "load.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "select", sample_data_path, "-c", "user_id,country",
+            "-o", output_path, "--output-delimiter", "|", "--output-no-header"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            lines = f.read().splitlines()
+        assert len(lines) == 500
+        assert lines[0] == "664|DE"
+    
     def test_select_command(self, sample_data_path, temp_dir):
         """Test select command"""
         output_path = os.path.join(temp_dir, "selected.parquet")
@@ -80,6 +301,49 @@ class TestCLI:
         assert os.path.exists(output_path)
         assert os.path.getsize(output_path) > 0
     
+    def test_select_patterns(self, temp_dir):
+        """Test glob, regex and --exclude column selection"""
+        input_path = os.path.join(temp_dir, "wide.csv")
+        with open(input_path, "w") as f:
+            f.write("id,sales_q1,sales_q2,f1,f22,fx,note\n1,2,3,4,5,6,x\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "select", input_path, "-c", "id,sales_*,re:^f[0-9]+$",
+            "--exclude", "sales_q2", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.readline().strip() == "id,sales_q1,f1,f22"
+        
+        output_path = os.path.join(temp_dir, "rest.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "select", input_path, "--exclude", "f*,note", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.readline().strip() == "id,sales_q1,sales_q2"
+    
+    def test_select_by_dtype(self, sample_data_path, temp_dir):
+        """Test selecting columns by dtype"""
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "select", sample_data_path, "--dtype", "string", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.readline().strip() == "country,channel"
+    
+    def test_select_rename_reorder(self, sample_data_path, temp_dir):
+        """Test old:new renames and requested column order"""
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "select", sample_data_path, "-c", "amount,user_id:customer_id",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.readline().strip() == "amount,customer_id"
+    
     def test_filter_command(self, sample_data_path, temp_dir):
         """Test filter command"""
         output_path = os.path.join(temp_dir, "filtered.parquet")
@@ -102,6 +366,1459 @@ class TestCLI:
         assert os.path.exists(output_path)
         assert os.path.getsize(output_path) > 0
     
+    def test_filter_multiple_where(self, temp_dir):
+        """Test ANDed --where clauses combined with an --or-where group"""
+        input_path = os.path.join(temp_dir, "in.csv")
+        with open(input_path, "w") as f:
+            f.write("id,amount,country\n1,150,US\n2,50,US\n3,200,DE\n4,300,FR\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "filter", input_path, "-w", "amount > 100",
+            "--or-where", "country = 'US'", "--or-where", "country = 'DE'", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert [l.split(",")[0] for l in f.read().splitlines()] == ["id", "1", "3"]
+    
+    def test_filter_offset_limit(self, temp_dir):
+        """Test paging through matching rows"""
+        input_path = os.path.join(temp_dir, "in.csv")
+        with open(input_path, "w") as f:
+            f.write("id,amount\n1,150\n2,50\n3,200\n4,300\n5,400\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "filter", input_path, "-w", "amount > 100",
+            "--offset", "1", "--limit", "2", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,amount", "3,200", "4,300"]
+    
+    def test_unknown_column_suggestion(self, sample_data_path, temp_dir):
+        """Test misspelled columns produce a suggestion and the column list"""
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "filter", sample_data_path, "-w", "ammount > 100", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "did you mean `amount`?" in result.stderr.lower()
+        assert "user_id, amount, country" in result.stderr
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "select", sample_data_path, "-c", "user_id,contry", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "`country`" in result.stderr
+    
+    def test_dedup_command(self, temp_dir):
+        """Test dedup command keeping the last duplicate"""
+        input_path = os.path.join(temp_dir, "events.csv")
+        with open(input_path, "w") as f:
+            f.write("id,v\n1,a\n2,b\n1,c\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "dedup", input_path, "--subset", "id", "--keep", "last",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,v", "2,b", "1,c"]
+    
+    def test_sql_command(self, sample_data_path, temp_dir):
+        """Test sql command reading a quoted file path"""
+        output_path = os.path.join(temp_dir, "totals.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sql",
+            f"SELECT country, count(*) AS n FROM '{sample_data_path}' GROUP BY country ORDER BY country",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            lines = f.read().splitlines()
+        assert lines[0] == "country,n"
+        assert sum(int(l.split(",")[1]) for l in lines[1:]) == 500
+    
+    def test_pivot_command(self, temp_dir):
+        """Test pivot command summing values into wide columns"""
+        input_path = os.path.join(temp_dir, "long.csv")
+        with open(input_path, "w") as f:
+            f.write("date,category,amount\nd1,a,1\nd1,b,2\nd1,a,3\nd2,b,4\n")
+        output_path = os.path.join(temp_dir, "wide.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "pivot", input_path, "--index", "date", "--columns", "category",
+            "--values", "amount", "--agg", "sum", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["date,a,b", "d1,4,2", "d2,,4"]
+    
+    def test_concat_diagonal(self, temp_dir):
+        """Test concat command unioning drifting schemas"""
+        a = os.path.join(temp_dir, "a.csv")
+        b = os.path.join(temp_dir, "b.csv")
+        with open(a, "w") as f:
+            f.write("id,x\n1,10\n")
+        with open(b, "w") as f:
+            f.write("id,y\n2,20\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "concat", a, b, "--how", "diagonal", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,x,y", "1,10,", "2,,20"]
+    
+    def test_diff_command(self, temp_dir):
+        """Test diff command counting added, removed and changed rows"""
+        old = os.path.join(temp_dir, "old.csv")
+        new = os.path.join(temp_dir, "new.csv")
+        with open(old, "w") as f:
+            f.write("id,v\n1,a\n2,b\n3,c\n")
+        with open(new, "w") as f:
+            f.write("id,v\n1,a\n2,x\n4,d\n")
+        result = subprocess.run([
+            "./target/debug/dpa", "diff", old, new, "--key", "id"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "Added rows:   1" in result.stdout
+        assert "Removed rows: 1" in result.stdout
+        assert "Changed rows: 1" in result.stdout
+        assert "~ column v: 1 changed" in result.stdout
+    
+    def test_cast_command(self, temp_dir):
+        """Test cast command parsing dates and nulling bad numbers"""
+        input_path = os.path.join(temp_dir, "raw.csv")
+        with open(input_path, "w") as f:
+            f.write("amount,signup\n1.5,31/01/2024\noops,01/02/2024\n")
+        output_path = os.path.join(temp_dir, "typed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "cast", input_path, "--types", "amount=f64,signup=date",
+            "--date-format", "%d/%m/%Y", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["amount,signup", "1.5,2024-01-31", ",2024-02-01"]
+    
+    def test_fillna_command(self, temp_dir):
+        """Test fillna command with forward fill"""
+        input_path = os.path.join(temp_dir, "gaps.csv")
+        with open(input_path, "w") as f:
+            f.write("t,v\n1,10\n2,\n3,30\n")
+        output_path = os.path.join(temp_dir, "filled.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "fillna", input_path, "--strategy", "forward", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["t,v", "1,10", "2,10", "3,30"]
+    
+    def test_replace_command(self, temp_dir):
+        """Test replace command mapping sentinel values"""
+        input_path = os.path.join(temp_dir, "survey.csv")
+        with open(input_path, "w") as f:
+            f.write("id,status\n1,Y\n2,N/A\n3,N\n")
+        output_path = os.path.join(temp_dir, "clean.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "replace", input_path, "--column", "status",
+            "--map", "N/A=null,Y=yes,N=no", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,status", "1,yes", "2,", "3,no"]
+    
+    def test_explode_command(self, temp_dir):
+        """Test explode command on a JSONL array column"""
+        input_path = os.path.join(temp_dir, "events.jsonl")
+        with open(input_path, "w") as f:
+            f.write('{"id": 1, "tags": ["a", "b"]}\n{"id": 2, "tags": ["c"]}\n')
+        output_path = os.path.join(temp_dir, "tags.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "explode", input_path, "--columns", "tags", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,tags", "1,a", "1,b", "2,c"]
+    
+    def test_unnest_command(self, temp_dir):
+        """Test unnest command flattening a JSON object column"""
+        input_path = os.path.join(temp_dir, "events.jsonl")
+        with open(input_path, "w") as f:
+            f.write('{"id": 1, "payload": {"user": "u1", "n": 2}}\n')
+        output_path = os.path.join(temp_dir, "flat.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "unnest", input_path, "--columns", "payload", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,payload.user,payload.n", "1,u1,2"]
+    
+    def test_window_command(self, temp_dir):
+        """Test window command with a rolling sum per partition"""
+        input_path = os.path.join(temp_dir, "tx.csv")
+        with open(input_path, "w") as f:
+            f.write("user,ts,amount\na,1,1\nb,1,10\na,2,2\na,3,3\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "window", input_path, "--over", "user", "--order-by", "ts",
+            "--rolling", "2", "--agg", "sum:amount", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = f.read().splitlines()
+        assert rows[0] == "user,ts,amount,rolling_sum_amount"
+        assert sorted(rows[1:]) == ["a,1,1,1", "a,2,2,3", "a,3,3,5", "b,1,10,10"]
+    
+    def test_bin_command(self, temp_dir):
+        """Test bin command with explicit breaks and labels"""
+        input_path = os.path.join(temp_dir, "people.csv")
+        with open(input_path, "w") as f:
+            f.write("age\n10\n18\n40\n70\n")
+        output_path = os.path.join(temp_dir, "binned.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "bin", input_path, "--column", "age", "--breaks", "18,65",
+            "--labels", "minor,adult,senior", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "age,age_bin", "10,minor", "18,minor", "40,adult", "70,senior"
+            ]
+    
+    def test_encode_onehot(self, temp_dir):
+        """Test encode command producing one-hot columns"""
+        input_path = os.path.join(temp_dir, "plans.csv")
+        with open(input_path, "w") as f:
+            f.write("id,plan\n1,pro\n2,free\n3,pro\n")
+        output_path = os.path.join(temp_dir, "encoded.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "encode", input_path, "--columns", "plan", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,plan_free,plan_pro", "1,0,1", "2,1,0", "3,0,1"]
+    
+    def test_scale_roundtrip(self, temp_dir):
+        """Test scale command fitting minmax parameters and reapplying them"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        with open(train, "w") as f:
+            f.write("x\n0\n5\n10\n")
+        with open(test, "w") as f:
+            f.write("x\n20\n")
+        params = os.path.join(temp_dir, "scaler.json")
+        out_train = os.path.join(temp_dir, "train_scaled.csv")
+        out_test = os.path.join(temp_dir, "test_scaled.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "scale", train, "--columns", "x", "--method", "minmax",
+            "--params-out", params, "-o", out_train
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(out_train) as f:
+            assert f.read().splitlines() == ["x", "0.0", "0.5", "1.0"]
+        result = subprocess.run([
+            "./target/debug/dpa", "scale", test, "--params-in", params, "-o", out_test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(out_test) as f:
+            assert f.read().splitlines() == ["x", "2.0"]
+        result = subprocess.run([
+            "./target/debug/dpa", "scale", train, "--columns", "x", "--method", "minmax",
+            "--params-out", params, "-o", os.path.join(temp_dir, "again.csv")
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "--force" in result.stderr
+    
+    def test_mask_command(self, temp_dir):
+        """Test mask command with the last4 and sha256 methods"""
+        import hashlib
+        input_path = os.path.join(temp_dir, "pii.csv")
+        with open(input_path, "w") as f:
+            f.write("email,card\na@x.io,4111222233334444\n")
+        output_path = os.path.join(temp_dir, "masked.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "mask", input_path, "--columns", "card", "--method", "last4", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["email,card", "a@x.io,****4444"]
+        result = subprocess.run([
+            "./target/debug/dpa", "mask", input_path, "--columns", "email", "--salt", "s1",
+            "-o", output_path, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        expected = hashlib.sha256(b"s1a@x.io").hexdigest()
+        with open(output_path) as f:
+            assert f.read().splitlines()[1] == f"{expected},4111222233334444"
+    
+    def test_extract_command(self, temp_dir):
+        """Test extract command with multiple capture groups"""
+        input_path = os.path.join(temp_dir, "urls.csv")
+        with open(input_path, "w") as f:
+            f.write("url\nhttps://example.com/docs/x\nnot a url\n")
+        output_path = os.path.join(temp_dir, "parsed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "extract", input_path, "--column", "url",
+            "--pattern", "https?://([^/]+)/([^/]+)", "--into", "domain,section", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "url,domain,section", "https://example.com/docs/x,example.com,docs", "not a url,,"
+            ]
+    
+    def test_split_col_command(self, temp_dir):
+        """Test split-col command with a typed output column"""
+        input_path = os.path.join(temp_dir, "codes.csv")
+        with open(input_path, "w") as f:
+            f.write("code\nA-10\nB-7\n")
+        output_path = os.path.join(temp_dir, "split.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split-col", input_path, "--column", "code", "--by", "-",
+            "--into", "letter,num", "--types", "num=int64", "--drop", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["letter,num", "A,10", "B,7"]
+    
+    def test_concat_col_command(self, temp_dir):
+        """Test concat-col command joining two columns"""
+        input_path = os.path.join(temp_dir, "names.csv")
+        with open(input_path, "w") as f:
+            f.write("first,last\nAda,Lovelace\n")
+        output_path = os.path.join(temp_dir, "joined.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "concat-col", input_path, "--columns", "first,last",
+            "--sep", " ", "--into", "full_name", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["first,last,full_name", "Ada,Lovelace,Ada Lovelace"]
+    
+    def test_parse_dates_reports_failures(self, temp_dir):
+        """Test parse-dates command warning about and nulling unparseable values"""
+        input_path = os.path.join(temp_dir, "dates.csv")
+        with open(input_path, "w") as f:
+            f.write("d\n2024-01-31\nyesterday\n")
+        output_path = os.path.join(temp_dir, "parsed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "parse-dates", input_path, "--columns", "d",
+            "--format", "%Y-%m-%d", "--date", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "1 unparseable" in result.stderr
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["d", "2024-01-31", ""]
+        result = subprocess.run([
+            "./target/debug/dpa", "parse-dates", input_path, "--columns", "d",
+            "--format", "%Y-%m-%d", "--date", "--strict", "-o", output_path, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+    
+    def test_derive_command(self, temp_dir):
+        """Test derive command chaining two expressions"""
+        input_path = os.path.join(temp_dir, "sales.csv")
+        with open(input_path, "w") as f:
+            f.write("revenue,cost\n10,4\n")
+        output_path = os.path.join(temp_dir, "derived.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "derive", input_path, "--expr", "profit = revenue - cost",
+            "--expr", "double = profit * 2", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["revenue,cost,profit,double", "10,4,6,12"]
+    
+    def test_top_per_group(self, temp_dir):
+        """Test top command keeping the largest row per group"""
+        input_path = os.path.join(temp_dir, "scores.csv")
+        with open(input_path, "w") as f:
+            f.write("team,score\na,1\nb,5\na,9\nb,2\n")
+        output_path = os.path.join(temp_dir, "top.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "top", input_path, "--by", "score", "--k", "1", "--per", "team", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["team,score", "a,9", "b,5"]
+    
+    def test_crosstab_command(self, temp_dir):
+        """Test crosstab command filling missing combinations with zero"""
+        input_path = os.path.join(temp_dir, "plans.csv")
+        with open(input_path, "w") as f:
+            f.write("region,plan\neu,pro\neu,free\nus,pro\neu,pro\n")
+        output_path = os.path.join(temp_dir, "xtab.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "crosstab", input_path, "--rows", "region", "--cols", "plan", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["region,free,pro", "eu,1,2", "us,0,1"]
+    
+    def test_corr_command(self, temp_dir):
+        """Test corr command on perfectly (anti-)correlated columns"""
+        input_path = os.path.join(temp_dir, "nums.csv")
+        with open(input_path, "w") as f:
+            f.write("a,b,label\n1,10,x\n2,8,y\n3,6,z\n")
+        output_path = os.path.join(temp_dir, "corr.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "corr", input_path, "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            lines = f.read().splitlines()
+        assert lines[0] == "column,a,b"
+        assert [round(float(v), 6) for v in lines[1].split(",")[1:]] == [1.0, -1.0]
+    
+    def test_value_counts_command(self, temp_dir):
+        """Test value-counts command ordering and proportions"""
+        input_path = os.path.join(temp_dir, "status.csv")
+        with open(input_path, "w") as f:
+            f.write("status\nok\nfail\nok\nok\n")
+        output_path = os.path.join(temp_dir, "counts.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "value-counts", input_path, "--column", "status", "--normalize", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["status,count,proportion", "ok,3,0.75", "fail,1,0.25"]
+    
+    def test_shuffle_is_seeded(self, temp_dir):
+        """Test shuffle command keeps all rows and repeats with the same seed"""
+        input_path = os.path.join(temp_dir, "ids.csv")
+        with open(input_path, "w") as f:
+            f.write("id\n" + "".join(f"{i}\n" for i in range(50)))
+        outputs = []
+        for name in ["a.csv", "b.csv"]:
+            output_path = os.path.join(temp_dir, name)
+            result = subprocess.run([
+                "./target/debug/dpa", "shuffle", input_path, "--seed", "42", "-o", output_path
+            ], capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                outputs.append(f.read().splitlines())
+        assert outputs[0] == outputs[1]
+        assert sorted(int(v) for v in outputs[0][1:]) == list(range(50))
+    
+    def test_transpose_command(self, temp_dir):
+        """Test transpose command naming columns from a header column"""
+        input_path = os.path.join(temp_dir, "summary.csv")
+        with open(input_path, "w") as f:
+            f.write("metric,q1,q2\nrevenue,10,20\ncost,4,5\n")
+        output_path = os.path.join(temp_dir, "flipped.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "transpose", input_path, "--header-column", "metric", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["column,revenue,cost", "q1,10,4", "q2,20,5"]
+    
+    def test_add_index_command(self, temp_dir):
+        """Test add-index command with a custom start"""
+        input_path = os.path.join(temp_dir, "rows.csv")
+        with open(input_path, "w") as f:
+            f.write("v\na\nb\n")
+        output_path = os.path.join(temp_dir, "indexed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "add-index", input_path, "--start", "1", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["row_id,v", "1,a", "2,b"]
+    
+    def test_clip_command(self, temp_dir):
+        """Test clip command keeps integer columns integral"""
+        input_path = os.path.join(temp_dir, "amounts.csv")
+        with open(input_path, "w") as f:
+            f.write("amount\n-5\n50\n500\n")
+        output_path = os.path.join(temp_dir, "clipped.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "clip", input_path, "--column", "amount", "--min", "0", "--max", "100",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["amount", "0", "50", "100"]
+    
+    def test_cum_command(self, temp_dir):
+        """Test cum command running totals per partition"""
+        input_path = os.path.join(temp_dir, "ledger.csv")
+        with open(input_path, "w") as f:
+            f.write("account,ts,amount\na,3,5\nb,2,7\na,1,10\n")
+        output_path = os.path.join(temp_dir, "running.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "cum", input_path, "--over", "account", "--order-by", "ts",
+            "--ops", "cumsum:amount,cumcount", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "account,ts,amount,cumsum_amount,cumcount", "a,1,10,10,1", "b,2,7,7,1", "a,3,5,15,2"
+            ]
+    
+    def test_rank_command(self, temp_dir):
+        """Test rank command with ties under the min method"""
+        input_path = os.path.join(temp_dir, "scores.csv")
+        with open(input_path, "w") as f:
+            f.write("score\n30\n10\n30\n20\n")
+        output_path = os.path.join(temp_dir, "ranked.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "rank", input_path, "--by", "score", "--method", "min",
+            "--descending", "--into", "rank", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["score,rank", "30,1", "10,4", "30,1", "20,3"]
+    
+    def test_pct_change_command(self, temp_dir):
+        """Test pct-change command on an ordered series"""
+        input_path = os.path.join(temp_dir, "monthly.csv")
+        with open(input_path, "w") as f:
+            f.write("month,revenue\n2,150\n1,100\n3,75\n")
+        output_path = os.path.join(temp_dir, "growth.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "pct-change", input_path, "--column", "revenue", "--order-by", "month",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "month,revenue,revenue_pct_change", "1,100,", "2,150,0.5", "3,75,-0.5"
+            ]
+    
+    def test_interpolate_command(self, temp_dir):
+        """Test interpolate command fills interior gaps in time order"""
+        input_path = os.path.join(temp_dir, "readings.csv")
+        with open(input_path, "w") as f:
+            f.write("ts,temp\n3,30\n1,10\n2,\n")
+        output_path = os.path.join(temp_dir, "filled.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "interpolate", input_path, "--columns", "temp", "--over", "ts", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["ts,temp", "1,10.0", "2,20.0", "3,30.0"]
+    
+    def test_round_command(self, temp_dir):
+        """Test round command in floor mode"""
+        input_path = os.path.join(temp_dir, "amounts.csv")
+        with open(input_path, "w") as f:
+            f.write("amount\n1.239\n-1.231\n")
+        output_path = os.path.join(temp_dir, "rounded.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "round", input_path, "--columns", "amount=2", "--mode", "floor", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["amount", "1.23", "-1.24"]
+    
+    def test_lag_command(self, temp_dir):
+        """Test lag command with lag and diff per partition"""
+        input_path = os.path.join(temp_dir, "orders.csv")
+        with open(input_path, "w") as f:
+            f.write("customer,day,amount\na,1,10\na,2,15\nb,1,7\n")
+        output_path = os.path.join(temp_dir, "features.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "lag", input_path, "--column", "amount", "--kinds", "lag,diff",
+            "--over", "customer", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "customer,day,amount,amount_lag1,amount_diff1", "a,1,10,,", "a,2,15,10,5", "b,1,7,,"
+            ]
+    
+    def test_clean_text_command(self, temp_dir):
+        """Test clean-text command chaining several ops"""
+        input_path = os.path.join(temp_dir, "cities.csv")
+        with open(input_path, "w", encoding="utf-8") as f:
+            f.write("city\n  São   Paulo \n")
+        output_path = os.path.join(temp_dir, "clean.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "clean-text", input_path, "--columns", "city",
+            "--ops", "trim,lower,strip-accents,collapse-spaces", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path, encoding="utf-8") as f:
+            assert f.read().splitlines() == ["city", "sao paulo"]
+    
+    def test_replace_re_command(self, temp_dir):
+        """Test replace-re command stripping non-digits"""
+        input_path = os.path.join(temp_dir, "contacts.csv")
+        with open(input_path, "w") as f:
+            f.write('phone\n"(555) 123-4567"\n')
+        output_path = os.path.join(temp_dir, "phones.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "replace-re", input_path, "--column", "phone", "--pattern", "[^0-9]",
+            "--with", "", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["phone", "5551234567"]
+    
+    def test_tokens_command(self, temp_dir):
+        """Test tokens command counting unigrams and bigrams"""
+        input_path = os.path.join(temp_dir, "feedback.csv")
+        with open(input_path, "w") as f:
+            f.write("comment\nGreat app!\ngreat support\n")
+        output_path = os.path.join(temp_dir, "tokens.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "tokens", input_path, "--column", "comment", "--ngram", "1,2",
+            "--top", "2", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["token,n,count", "great,1,2", "app,1,1"]
+    
+    def test_compact_command(self, sample_data_path, temp_dir):
+        """Test compact command merging chunked part files"""
+        parts_dir = os.path.join(temp_dir, "parts")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", sample_data_path, parts_dir, "--max-rows-per-file", "200"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert len(os.listdir(parts_dir)) > 1
+        out_dir = os.path.join(temp_dir, "compacted")
+        result = subprocess.run([
+            "./target/debug/dpa", "compact", parts_dir, "-o", out_dir
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert os.listdir(out_dir) == ["part-00000.parquet"]
+    
+    def test_repartition_hive(self, temp_dir):
+        """Test repartition command writes one sorted directory per key"""
+        input_path = os.path.join(temp_dir, "events.csv")
+        with open(input_path, "w") as f:
+            f.write("country,day\nde,2\nfr,1\nde,1\n")
+        out_dir = os.path.join(temp_dir, "by_country")
+        result = subprocess.run([
+            "./target/debug/dpa", "repartition", input_path, "--by", "country", "--sort-within", "day",
+            "--output-format", "csv", "-o", out_dir
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert sorted(os.listdir(out_dir)) == ["country=de", "country=fr"]
+        with open(os.path.join(out_dir, "country=de", "part-00000.csv")) as f:
+            assert f.read().splitlines() == ["day", "1", "2"]
+    
+    def test_repartition_force_clears_stale_parts(self, sample_data_path, temp_dir):
+        """Test repartition --force replaces its own previous output but nothing else"""
+        out_dir = os.path.join(temp_dir, "parts")
+        cmd = ["./target/debug/dpa", "repartition", sample_data_path, "--by", "country", "-o", out_dir]
+        result = subprocess.run(cmd + ["--partitions", "3"], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert len(os.listdir(out_dir)) == 3
+        result = subprocess.run(cmd + ["--partitions", "1", "--force"], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert os.listdir(out_dir) == ["part-00000.parquet"]
+        
+        with open(os.path.join(out_dir, "notes.txt"), "w") as f:
+            f.write("keep me\n")
+        result = subprocess.run(cmd + ["--force"], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "notes.txt" in result.stderr
+        assert sorted(os.listdir(out_dir)) == ["notes.txt", "part-00000.parquet"]
+    
+    def test_convert_dir_command(self, temp_dir):
+        """Test convert-dir command mirrors nested directories"""
+        src = os.path.join(temp_dir, "raw")
+        os.makedirs(os.path.join(src, "2024"))
+        for rel in ["a.csv", os.path.join("2024", "b.csv")]:
+            with open(os.path.join(src, rel), "w") as f:
+                f.write("x\n1\n")
+        dst = os.path.join(temp_dir, "curated")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert-dir", src, dst, "--from", "csv", "--to", "parquet", "--jobs", "2"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert os.path.exists(os.path.join(dst, "a.parquet"))
+        assert os.path.exists(os.path.join(dst, "2024", "b.parquet"))
+    
+    def test_validate_command(self, temp_dir):
+        """Test validate command reports failed checks with a non-zero exit"""
+        input_path = os.path.join(temp_dir, "users.csv")
+        with open(input_path, "w") as f:
+            f.write("id,email\n1,a@x.io\n1,\n")
+        schema_path = os.path.join(temp_dir, "schema.json")
+        with open(schema_path, "w") as f:
+            f.write('{"id": "Int64", "email": "String", "age": "Int64"}')
+        result = subprocess.run([
+            "./target/debug/dpa", "validate", input_path, "--schema", schema_path,
+            "--not-null", "email", "--unique", "id"
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "missing column age" in result.stdout
+        assert "email has 1 null" in result.stdout
+        assert "id has 1 duplicate" in result.stdout
+    
+    def test_split_command(self, sample_data_path, temp_dir):
+        """Test split command produces disjoint sets of the requested sizes"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split", sample_data_path, "--test-size", "0.2", "--seed", "7",
+            "--train", train, "--test", test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(train) as f:
+            train_rows = f.read().splitlines()[1:]
+        with open(test) as f:
+            test_rows = f.read().splitlines()[1:]
+        assert (len(train_rows), len(test_rows)) == (400, 100)
+        with open(sample_data_path) as f:
+            all_rows = f.read().splitlines()[1:]
+        assert sorted(train_rows + test_rows) == sorted(all_rows)
+    
+    def test_sample_reservoir(self, sample_data_path, temp_dir):
+        """Test reservoir sampling is seeded, sized and keeps input order"""
+        outputs = []
+        for i in range(2):
+            output_path = os.path.join(temp_dir, f"sample{i}.csv")
+            result = subprocess.run([
+                "./target/debug/dpa", "sample", sample_data_path, "--size", "25",
+                "--method", "reservoir", "--seed", "3", "-o", output_path
+            ], capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                outputs.append(f.read().splitlines()[1:])
+        assert outputs[0] == outputs[1]
+        assert len(outputs[0]) == 25
+        with open(sample_data_path) as f:
+            all_rows = f.read().splitlines()[1:]
+        positions = [all_rows.index(r) for r in outputs[0]]
+        assert positions == sorted(positions)
+    
+    def test_sample_systematic(self, temp_dir):
+        """Test systematic sampling takes every Nth row from the offset"""
+        input_path = os.path.join(temp_dir, "log.csv")
+        with open(input_path, "w") as f:
+            f.write("seq\n" + "".join(f"{i}\n" for i in range(20)))
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sample", input_path, "--method", "systematic",
+            "--every", "5", "--offset", "2", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["seq", "2", "7", "12", "17"]
+    
+    def test_sample_weighted(self, temp_dir):
+        """Test weighted sampling never draws zero-weight rows"""
+        input_path = os.path.join(temp_dir, "accounts.csv")
+        with open(input_path, "w") as f:
+            f.write("id,importance\n" + "".join(f"{i},{i % 2}\n" for i in range(40)))
+        for extra in ([], ["--replace"]):
+            output_path = os.path.join(temp_dir, "out.csv")
+            result = subprocess.run([
+                "./target/debug/dpa", "sample", input_path, "--size", "10", "--method", "weighted",
+                "--weight-column", "importance", "--seed", "1", "-o", output_path, "--force"
+            ] + extra, capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                rows = [l.split(",") for l in f.read().splitlines()[1:]]
+            assert len(rows) == 10
+            assert all(r[1] == "1" for r in rows)
+            if not extra:
+                assert len({r[0] for r in rows}) == 10
+    
+    def test_sample_fraction(self, sample_data_path, temp_dir):
+        """Test --fraction draws a share of rows"""
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sample", sample_data_path, "--fraction", "0.1",
+            "--seed", "4", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert len(f.read().splitlines()) == 51
+        
+        output_path = os.path.join(temp_dir, "streamed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sample", sample_data_path, "--fraction", "0.5",
+            "--method", "reservoir", "--seed", "4", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert 150 < len(f.read().splitlines()) - 1 < 350
+    
+    def test_sample_cluster(self, temp_dir):
+        """Test cluster sampling keeps every row of the chosen groups"""
+        input_path = os.path.join(temp_dir, "events.csv")
+        with open(input_path, "w") as f:
+            f.write("user_id,step\n" + "".join(f"{u},{s}\n" for u in range(10) for s in range(3)))
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sample", input_path, "--method", "cluster",
+            "--cluster-column", "user_id", "--clusters", "4", "--seed", "9", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = [l.split(",") for l in f.read().splitlines()[1:]]
+        users = {r[0] for r in rows}
+        assert len(users) == 4
+        assert all([r[1] for r in rows if r[0] == u] == ["0", "1", "2"] for u in users)
+    
+    def test_sample_bootstrap(self, temp_dir):
+        """Test bootstrap resamples are full-size and numbered"""
+        input_path = os.path.join(temp_dir, "in.csv")
+        with open(input_path, "w") as f:
+            f.write("x\n" + "".join(f"{i}\n" for i in range(20)))
+        output_path = os.path.join(temp_dir, "boot.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "sample", input_path, "--method", "bootstrap",
+            "--resamples", "3", "--seed", "5", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        for i in range(3):
+            with open(os.path.join(temp_dir, f"boot_{i:04}.csv")) as f:
+                values = f.read().splitlines()[1:]
+            assert len(values) == 20
+            assert set(values) <= {str(i) for i in range(20)}
+        assert not os.path.exists(output_path)
+    
+    def test_split_unseeded_reports_seed(self, sample_data_path, temp_dir):
+        """Test an unseeded split is disjoint and reproducible from the printed seed"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split", sample_data_path, "--train", train, "--test", test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        seed = result.stdout.strip().rsplit("seed: ", 1)[1]
+        with open(train) as f:
+            train_rows = f.read().splitlines()[1:]
+        with open(test) as f:
+            test_rows = f.read().splitlines()[1:]
+        assert (len(train_rows), len(test_rows)) == (400, 100)
+        assert not set(train_rows) & set(test_rows)
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "split", sample_data_path, "--seed", seed,
+            "--train", train, "--test", test, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(test) as f:
+            assert f.read().splitlines()[1:] == test_rows
+    
+    def test_kfold_force_drops_old_folds(self, sample_data_path, temp_dir):
+        """Test rerunning kfold with fewer folds and --force removes the extra folds"""
+        output_dir = os.path.join(temp_dir, "folds")
+        cmd = ["./target/debug/dpa", "kfold", sample_data_path, "--seed", "1", "-o", output_dir]
+        result = subprocess.run(cmd + ["--folds", "5"], capture_output=True, text=True)
+        assert result.returncode == 0
+        result = subprocess.run(cmd + ["--folds", "3", "--force"], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert sorted(os.listdir(output_dir)) == ["fold_0", "fold_1", "fold_2"]
+    
+    def test_kfold_command(self, sample_data_path, temp_dir):
+        """Test kfold writes folds whose test sets partition the input"""
+        output_dir = os.path.join(temp_dir, "folds")
+        result = subprocess.run([
+            "./target/debug/dpa", "kfold", sample_data_path, "--folds", "4", "--stratify", "country",
+            "--seed", "42", "--output-format", "csv", "-o", output_dir
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(sample_data_path) as f:
+            all_rows = sorted(f.read().splitlines()[1:])
+        tests = []
+        for i in range(4):
+            with open(os.path.join(output_dir, f"fold_{i}", "train.csv")) as f:
+                train_rows = f.read().splitlines()[1:]
+            with open(os.path.join(output_dir, f"fold_{i}", "test.csv")) as f:
+                test_rows = f.read().splitlines()[1:]
+            assert len(test_rows) == 125
+            assert sorted(train_rows + test_rows) == all_rows
+            tests += test_rows
+        assert sorted(tests) == all_rows
+    
+    def test_split_by_time(self, temp_dir):
+        """Test time split keeps every test row newer than every train row"""
+        input_path = os.path.join(temp_dir, "events.csv")
+        with open(input_path, "w") as f:
+            f.write("event_ts,v\n2024-01-03,3\n2023-12-30,1\n2024-01-01,2\n2024-01-05,4\n")
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split", input_path, "--method", "time", "--time-column", "event_ts",
+            "--cutoff", "2024-01-02", "--train", train, "--test", test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(train) as f:
+            assert sorted(f.read().splitlines()[1:]) == ["2023-12-30,1", "2024-01-01,2"]
+        with open(test) as f:
+            assert sorted(f.read().splitlines()[1:]) == ["2024-01-03,3", "2024-01-05,4"]
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "split", input_path, "--method", "time", "--time-column", "event_ts",
+            "--test-size", "0.25", "--train", train, "--test", test, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(test) as f:
+            assert f.read().splitlines()[1:] == ["2024-01-05,4"]
+    
+    def test_split_by_group(self, temp_dir):
+        """Test group-aware split never puts a group on both sides"""
+        input_path = os.path.join(temp_dir, "orders.csv")
+        with open(input_path, "w") as f:
+            f.write("customer_id,order_id\n" + "".join(f"{c},{c * 10 + o}\n" for c in range(10) for o in range(4)))
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split", input_path, "--group-column", "customer_id",
+            "--test-size", "0.3", "--seed", "2", "--train", train, "--test", test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(train) as f:
+            train_groups = [l.split(",")[0] for l in f.read().splitlines()[1:]]
+        with open(test) as f:
+            test_groups = [l.split(",")[0] for l in f.read().splitlines()[1:]]
+        assert len(set(test_groups)) == 3
+        assert not set(train_groups) & set(test_groups)
+        assert len(train_groups) + len(test_groups) == 40
+    
+    def test_split_stratify_bins(self, sample_data_path, temp_dir):
+        """Test binned stratification keeps the amount distribution in both sets"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "split", sample_data_path, "--stratify", "amount",
+            "--stratify-bins", "5", "--test-size", "0.2", "--seed", "3", "--train", train, "--test", test
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(sample_data_path) as f:
+            amounts = sorted(float(l.split(",")[1]) for l in f.read().splitlines()[1:])
+        with open(test) as f:
+            lines = f.read().splitlines()
+        assert lines[0] == "user_id,amount,country,timestamp,channel"
+        test_amounts = [float(l.split(",")[1]) for l in lines[1:]]
+        assert len(test_amounts) == 100
+        # 20 test rows from each quintile of the full data
+        edges = [amounts[i * 100] for i in range(1, 5)]
+        counts = [sum(1 for a in test_amounts if lo <= a < hi)
+                  for lo, hi in zip([float("-inf")] + edges, edges + [float("inf")])]
+        assert all(15 <= c <= 25 for c in counts)
+    
+    def test_balance_command(self, temp_dir):
+        """Test balance reports class counts and undersamples to equal classes"""
+        input_path = os.path.join(temp_dir, "labeled.csv")
+        with open(input_path, "w") as f:
+            f.write("id,label\n" + "".join(f"{i},{'spam' if i % 5 == 0 else 'ham'}\n" for i in range(50)))
+        result = subprocess.run([
+            "./target/debug/dpa", "balance", input_path, "--target", "label"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "ham: 40 (80.0%)" in result.stdout
+        assert "spam: 10 (20.0%)" in result.stdout
+        
+        for method, per_class in (("undersample", 10), ("oversample", 40)):
+            output_path = os.path.join(temp_dir, f"{method}.csv")
+            result = subprocess.run([
+                "./target/debug/dpa", "balance", input_path, "--target", "label",
+                "--method", method, "--seed", "1", "-o", output_path
+            ], capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                labels = [l.split(",")[1] for l in f.read().splitlines()[1:]]
+            assert labels.count("ham") == labels.count("spam") == per_class
+    
+    def test_leak_check(self, temp_dir):
+        """Test leak-check passes disjoint splits and fails on shared rows"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        with open(train, "w") as f:
+            f.write("id,text\n1,Hello World\n2,foo\n")
+        with open(test, "w") as f:
+            f.write("id,text\n3,hello  world\n4,bar\n")
+        result = subprocess.run(["./target/debug/dpa", "leak-check", train, test, "--key", "id"],
+                                capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "0 of 2 test rows" in result.stdout
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "leak-check", train, test, "--key", "text", "--near-duplicate"
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "Exact overlap on key text: 0 of 2" in result.stdout
+        assert "Near-duplicate overlap on key text: 1 of 2" in result.stdout
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "leak-check", train, test, "--key", "text", "--near-duplicate",
+            "--max-overlap", "0.5"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+    
+    def test_leak_check_matches_nulls(self, temp_dir):
+        """Test leak-check reports a duplicated row even when it has a missing value"""
+        train = os.path.join(temp_dir, "train.csv")
+        test = os.path.join(temp_dir, "test.csv")
+        with open(train, "w") as f:
+            f.write("id,score,label\n1,,x\n2,5,y\n")
+        with open(test, "w") as f:
+            f.write("id,score,label\n1,,x\n3,7,z\n")
+        result = subprocess.run(["./target/debug/dpa", "leak-check", train, test],
+                                capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "Exact overlap on all shared columns: 1 of 2" in result.stdout
+    
+    def test_tail_command(self, temp_dir):
+        """Test tail command prints the last rows"""
+        input_path = os.path.join(temp_dir, "log.csv")
+        with open(input_path, "w") as f:
+            f.write("seq\n" + "".join(f"{i}\n" for i in range(100, 120)))
+        result = subprocess.run([
+            "./target/debug/dpa", "tail", input_path, "-n", "2"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "118" in result.stdout and "119" in result.stdout
+        assert "117" not in result.stdout
+    
+    def test_head_markdown_format(self, temp_dir):
+        """Test head command rendering a markdown table"""
+        input_path = os.path.join(temp_dir, "notes.csv")
+        with open(input_path, "w") as f:
+            f.write("id,note\n1,a very long note indeed\n")
+        result = subprocess.run([
+            "./target/debug/dpa", "head", input_path, "--format", "markdown", "--max-col-width", "6"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert result.stdout.splitlines() == ["| id | note |", "|---|---|", "| 1 | a ver… |"]
+    
+    def test_schema_save_roundtrip(self, sample_data_path, temp_dir):
+        """Test schema --save output is accepted by validate --schema"""
+        import json
+        schema_path = os.path.join(temp_dir, "schema.json")
+        result = subprocess.run([
+            "./target/debug/dpa", "schema", sample_data_path, "--format", "json", "--save", schema_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(schema_path) as f:
+            schema = json.load(f)
+        assert schema["user_id"] == "Int64"
+        assert json.loads(result.stdout) == schema
+        result = subprocess.run([
+            "./target/debug/dpa", "validate", sample_data_path, "--schema", schema_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+    
+    def test_schema_diff_command(self, temp_dir):
+        """Test schema-diff tolerates widening but fails on removed columns"""
+        old = os.path.join(temp_dir, "old.csv")
+        wide = os.path.join(temp_dir, "wide.csv")
+        narrow = os.path.join(temp_dir, "narrow.csv")
+        with open(old, "w") as f:
+            f.write("id,amount\n1,5\n")
+        with open(wide, "w") as f:
+            f.write("id,amount,note\n1,5.5,x\n")
+        with open(narrow, "w") as f:
+            f.write("id\n1\n")
+        result = subprocess.run(["./target/debug/dpa", "schema-diff", old, wide], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert "+ column note" in result.stdout
+        assert "Int64 -> Float64 (widening)" in result.stdout
+        result = subprocess.run(["./target/debug/dpa", "schema-diff", old, narrow], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "- column amount" in result.stdout
+    
+    def test_agg_extra_aggregations(self, temp_dir):
+        """Test agg command with max, n-unique and quantile"""
+        input_path = os.path.join(temp_dir, "orders.csv")
+        with open(input_path, "w") as f:
+            f.write("region,user,amount\neu,1,10\neu,1,30\neu,2,20\n")
+        output_path = os.path.join(temp_dir, "agg.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "agg", input_path, "-g", "region", "--max", "amount",
+            "--n-unique", "user", "--quantile", "amount:0.5", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["region,max_amount,n_unique_user,p50_amount", "eu,30,2,20.0"]
+    
+    def test_agg_multiple_keys(self, temp_dir):
+        """Test agg command grouping by two repeated --group flags"""
+        input_path = os.path.join(temp_dir, "orders.csv")
+        with open(input_path, "w") as f:
+            f.write("region,month,amount\neu,2,5\neu,1,10\nus,1,7\neu,1,1\n")
+        output_path = os.path.join(temp_dir, "agg.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "agg", input_path, "-g", "region", "-g", "month", "--sum", "amount",
+            "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["region,month,sum_amount", "eu,1,11", "eu,2,5", "us,1,7"]
+        result = subprocess.run([
+            "./target/debug/dpa", "agg", input_path, "-g", "region,month", "--sum", "amount",
+            "--having", "sum_amount > 6", "-o", output_path, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["region,month,sum_amount", "eu,1,11", "us,1,7"]
+    
+    def test_agg_pivot(self, temp_dir):
+        """Test agg command spreading a pivot column into aggregate columns"""
+        input_path = os.path.join(temp_dir, "sales.csv")
+        with open(input_path, "w") as f:
+            f.write("region,category,amount\neu,toys,5\neu,food,10\nus,toys,7\neu,toys,1\n")
+        output_path = os.path.join(temp_dir, "report.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "agg", input_path, "-g", "region", "--sum", "amount",
+            "--pivot", "category", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "region,sum_amount_food,sum_amount_toys", "eu,10,6", "us,,7"
+            ]
+    
+    def test_join_anti(self, temp_dir):
+        """Test anti join keeping only unmatched left rows"""
+        orders = os.path.join(temp_dir, "orders.csv")
+        customers = os.path.join(temp_dir, "customers.csv")
+        with open(orders, "w") as f:
+            f.write("order_id,customer_id\n1,10\n2,11\n3,12\n")
+        with open(customers, "w") as f:
+            f.write("customer_id,name\n10,ana\n12,bo\n")
+        output_path = os.path.join(temp_dir, "orphans.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", orders, customers, "--on", "customer_id",
+            "--how", "anti", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["order_id,customer_id", "2,11"]
+    
+    def test_join_composite_key(self, temp_dir):
+        """Test join on two key columns"""
+        left = os.path.join(temp_dir, "orders.csv")
+        right = os.path.join(temp_dir, "shipments.csv")
+        with open(left, "w") as f:
+            f.write("customer_id,order_date,amount\n1,2024-01-01,5\n1,2024-01-02,7\n")
+        with open(right, "w") as f:
+            f.write("customer_id,order_date,carrier\n1,2024-01-02,ups\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", left, right, "--on", "customer_id",
+            "--on", "order_date", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "customer_id,order_date,amount,carrier", "1,2024-01-02,7,ups"
+            ]
+    
+    def test_join_left_right_on(self, temp_dir):
+        """Test join with differently named key columns"""
+        left = os.path.join(temp_dir, "orders.csv")
+        right = os.path.join(temp_dir, "customers.csv")
+        with open(left, "w") as f:
+            f.write("id,amount\n1,5\n2,7\n")
+        with open(right, "w") as f:
+            f.write("customer_id,name\n2,bo\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", left, right, "--left-on", "id",
+            "--right-on", "customer_id", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["id,amount,name", "2,7,bo"]
+    
+    def test_join_asof(self, temp_dir):
+        """Test as-of join picking the latest right row at or before each key"""
+        trades = os.path.join(temp_dir, "trades.csv")
+        quotes = os.path.join(temp_dir, "quotes.csv")
+        with open(trades, "w") as f:
+            f.write("ts,symbol,qty\n5,a,1\n12,a,2\n3,b,3\n")
+        with open(quotes, "w") as f:
+            f.write("ts,symbol,bid\n1,a,10\n10,a,11\n2,b,20\n")
+        output_path = os.path.join(temp_dir, "aligned.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", trades, quotes, "--how", "asof", "--on", "ts",
+            "--by", "symbol", "--tolerance", "3", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == [
+                "ts,symbol,qty,bid", "3,b,3,20", "5,a,1,", "12,a,2,11"
+            ]
+    
+    def test_join_fuzzy(self, temp_dir):
+        """Test fuzzy join matching approximate names"""
+        vendors = os.path.join(temp_dir, "vendors.csv")
+        master = os.path.join(temp_dir, "master.csv")
+        with open(vendors, "w") as f:
+            f.write("name,spend\nAcme Corp ,10\nGlobex Inc,20\nZzz,30\n")
+        with open(master, "w") as f:
+            f.write("name,vendor_id\nACME Corp,1\nGlobex Incorporated,2\n")
+        output_path = os.path.join(temp_dir, "matched.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", vendors, master, "--how", "fuzzy", "--on", "name",
+            "--similarity", "0.8", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = list(csv.DictReader(f))
+        assert [r["name_match"] for r in rows] == ["ACME Corp", "Globex Incorporated", ""]
+        assert float(rows[0]["match_score"]) == 1.0
+        assert rows[1]["vendor_id"] == "2"
+        assert rows[2]["match_score"] == ""
+    
+    def test_join_suffix_coalesce(self, temp_dir):
+        """Test full join with a custom suffix and coalesced keys"""
+        jan = os.path.join(temp_dir, "jan.csv")
+        feb = os.path.join(temp_dir, "feb.csv")
+        with open(jan, "w") as f:
+            f.write("sku,qty\na,1\nb,2\n")
+        with open(feb, "w") as f:
+            f.write("sku,qty\nb,5\nc,6\n")
+        output_path = os.path.join(temp_dir, "both.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", jan, feb, "--on", "sku", "--how", "full",
+            "--coalesce", "--suffix", "_feb", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = sorted(f.read().splitlines()[1:])
+            f.seek(0)
+            assert f.readline().strip() == "sku,qty,qty_feb"
+        assert rows == ["a,1,", "b,2,5", "c,,6"]
+    
+    def test_join_validate(self, temp_dir):
+        """Test join --validate rejecting duplicate keys on the one side"""
+        orders = os.path.join(temp_dir, "orders.csv")
+        customers = os.path.join(temp_dir, "customers.csv")
+        with open(orders, "w") as f:
+            f.write("order_id,customer_id\n1,10\n2,10\n")
+        with open(customers, "w") as f:
+            f.write("customer_id,name\n10,ana\n10,bo\n")
+        output_path = os.path.join(temp_dir, "out.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "join", orders, customers, "--on", "customer_id",
+            "--validate", "m:1", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "right file" in result.stderr
+        assert not os.path.exists(output_path)
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "join", customers, orders, "--on", "customer_id",
+            "--validate", "m:m", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+    
+    def test_join_broadcast(self, sample_data_path, temp_dir):
+        """Test broadcast join gives the same rows as a regular join"""
+        dim = os.path.join(temp_dir, "countries.csv")
+        with open(dim, "w") as f:
+            f.write("country,region\nUS,amer\nUK,emea\nDE,emea\nFR,emea\nJP,apac\nIN,apac\nCA,amer\nBR,amer\n")
+        outputs = []
+        for extra in ([], ["--broadcast", "right"]):
+            output_path = os.path.join(temp_dir, f"out{len(outputs)}.csv")
+            result = subprocess.run([
+                "./target/debug/dpa", "join", sample_data_path, dim, "--on", "country",
+                "--how", "left", "-o", output_path
+            ] + extra, capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                outputs.append(sorted(f.read().splitlines()))
+        assert outputs[0] == outputs[1]
+        assert len(outputs[0]) == 501
+    
+    def test_convert_xlsx_sheet_name(self, sample_data_path, temp_dir):
+        """Test --sheet-name names the worksheet of an .xlsx output"""
+        import zipfile
+        output_path = os.path.join(temp_dir, "report.xlsx")
+        result = subprocess.run([
+            "./target/debug/dpa", "convert", sample_data_path, output_path, "--sheet-name", "Sales"
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with zipfile.ZipFile(output_path) as z:
+            workbook = z.read("xl/workbook.xml").decode()
+        assert 'name="Sales"' in workbook
+        assert 'name="Sheet1"' not in workbook
+    
+    def test_convert_parquet_write_options(self, sample_data_path, temp_dir):
+        """Test Parquet written with --compression/--row-group-size/--no-statistics reads back intact"""
+        expected_path = os.path.join(temp_dir, "expected.csv")
+        result = subprocess.run(["./target/debug/dpa", "convert", sample_data_path, expected_path],
+                                capture_output=True, text=True)
+        assert result.returncode == 0
+        sizes = {}
+        for codec in ["zstd", "uncompressed"]:
+            parquet_path = os.path.join(temp_dir, f"{codec}.parquet")
+            result = subprocess.run([
+                "./target/debug/dpa", "convert", sample_data_path, parquet_path, "--compression", codec,
+                "--row-group-size", "100", "--no-statistics"
+            ], capture_output=True, text=True)
+            assert result.returncode == 0
+            sizes[codec] = os.path.getsize(parquet_path)
+            roundtrip_path = os.path.join(temp_dir, f"{codec}.csv")
+            result = subprocess.run(["./target/debug/dpa", "convert", parquet_path, roundtrip_path],
+                                    capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(roundtrip_path) as f, open(expected_path) as g:
+                assert f.read() == g.read()
+        assert sizes["zstd"] < sizes["uncompressed"]
+    
+    def test_melt_command(self, temp_dir):
+        """Test melt command unpivoting value columns under custom names"""
+        input_path = os.path.join(temp_dir, "wide.csv")
+        with open(input_path, "w") as f:
+            f.write("id,q1,q2\n1,10,20\n2,30,40\n")
+        output_path = os.path.join(temp_dir, "long.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "melt", input_path, "--id-vars", "id",
+            "--variable-name", "quarter", "--value-name", "sales", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = f.read().splitlines()
+        assert rows[0] == "id,quarter,sales"
+        assert sorted(rows[1:]) == ["1,q1,10", "1,q2,20", "2,q1,30", "2,q2,40"]
+    
+    def test_rename_command(self, temp_dir):
+        """Test rename command applying --map and --from-file renames"""
+        input_path = os.path.join(temp_dir, "raw.csv")
+        with open(input_path, "w") as f:
+            f.write("cust,amt,ts\n1,9.5,2024\n")
+        mapping_path = os.path.join(temp_dir, "names.csv")
+        with open(mapping_path, "w") as f:
+            f.write("old,new\nts,year\n")
+        output_path = os.path.join(temp_dir, "renamed.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "rename", input_path, "--map", "cust=customer_id,amt=amount",
+            "--from-file", mapping_path, "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["customer_id,amount,year", "1,9.5,2024"]
+    
+    def test_dropna_command(self, temp_dir):
+        """Test dropna command with the default, --how all and --subset"""
+        input_path = os.path.join(temp_dir, "gaps.csv")
+        with open(input_path, "w") as f:
+            f.write("a,b\n1,\n,\n3,4\n")
+        output_path = os.path.join(temp_dir, "clean.csv")
+        for extra, expected in [
+            ([], ["a,b", "3,4"]),
+            (["--how", "all"], ["a,b", "1,", "3,4"]),
+            (["--subset", "a"], ["a,b", "1,", "3,4"]),
+        ]:
+            result = subprocess.run([
+                "./target/debug/dpa", "dropna", input_path, "-o", output_path, "--force"
+            ] + extra, capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                assert f.read().splitlines() == expected
+    
+    def test_resample_buckets_and_gaps(self, temp_dir):
+        """Test resample sums per interval and --fill-gaps emits empty intervals"""
+        input_path = os.path.join(temp_dir, "events.csv")
+        with open(input_path, "w") as f:
+            f.write("ts,v\n2024-01-01 00:10:00,1\n2024-01-01 00:40:00,2\n2024-01-01 02:05:00,4\n")
+        expected = {(): [("00", "3"), ("02", "4")], ("--fill-gaps",): [("00", "3"), ("01", ""), ("02", "4")]}
+        for extra, buckets in expected.items():
+            output_path = os.path.join(temp_dir, f"hourly{len(extra)}.csv")
+            result = subprocess.run([
+                "./target/debug/dpa", "resample", input_path, "--time", "ts", "--every", "1h",
+                "--agg", "sum:v", "--dtypes", "ts=datetime", "-o", output_path
+            ] + list(extra), capture_output=True, text=True)
+            assert result.returncode == 0
+            with open(output_path) as f:
+                rows = list(csv.DictReader(f))
+            assert [(r["ts"][11:13], r["sum_v"]) for r in rows] == buckets
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "resample", input_path, "--time", "ts", "--every", "abc",
+            "--agg", "sum:v", "-o", os.path.join(temp_dir, "bad.csv")
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "Invalid --every interval" in result.stderr
+    
+    def test_bin_breaks_validation(self, temp_dir):
+        """Test bin merges repeated breaks, rejects non-finite ones and checks label counts"""
+        input_path = os.path.join(temp_dir, "ages.csv")
+        with open(input_path, "w") as f:
+            f.write("age\n10\n40\n")
+        output_path = os.path.join(temp_dir, "binned.csv")
+        cmd = ["./target/debug/dpa", "bin", input_path, "--column", "age", "-o", output_path, "--force"]
+        result = subprocess.run(cmd + ["--breaks", "18,18", "--labels", "minor,adult"],
+                                capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["age,age_bin", "10,minor", "40,adult"]
+        result = subprocess.run(cmd + ["--breaks", "18,nan"], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "not a finite number" in result.stderr
+        result = subprocess.run(cmd + ["--quantiles", "2", "--labels", "low,mid,high"],
+                                capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "--quantiles 2 yields 2 bins" in result.stderr
+    
+    def test_tz_command(self, temp_dir):
+        """Test tz command localizing naive timestamps and following DST"""
+        input_path = os.path.join(temp_dir, "utc.csv")
+        with open(input_path, "w") as f:
+            f.write("ts\n2024-01-15 12:00:00\n2024-07-15 12:00:00\n")
+        output_path = os.path.join(temp_dir, "ny.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "tz", input_path, "--columns", "ts", "--from", "UTC",
+            "--to", "America/New_York", "--dtypes", "ts=datetime", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            rows = f.read().splitlines()
+        assert [r[11:19] for r in rows[1:]] == ["07:00:00", "08:00:00"]
+        
+        result = subprocess.run([
+            "./target/debug/dpa", "tz", input_path, "--columns", "ts", "--to", "UTC",
+            "--dtypes", "ts=datetime", "-o", output_path, "--force"
+        ], capture_output=True, text=True)
+        assert result.returncode != 0
+        assert "--from" in result.stderr
+    
+    def test_repartition_escapes_values(self, temp_dir):
+        """Test repartition percent-encodes partition values that contain slashes"""
+        input_path = os.path.join(temp_dir, "paths.csv")
+        with open(input_path, "w") as f:
+            f.write("route,n\na/b,1\nc,2\n")
+        out_dir = os.path.join(temp_dir, "by_route")
+        result = subprocess.run([
+            "./target/debug/dpa", "repartition", input_path, "--by", "route",
+            "--output-format", "csv", "-o", out_dir
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        assert sorted(os.listdir(out_dir)) == ["route=a%2Fb", "route=c"]
+    
+    def test_repartition_partitions_keep_keys_together(self, sample_data_path, temp_dir):
+        """Test repartition --partitions only cuts files at key boundaries"""
+        out_dir = os.path.join(temp_dir, "parts")
+        result = subprocess.run([
+            "./target/debug/dpa", "repartition", sample_data_path, "--by", "country", "--partitions", "3",
+            "--output-format", "csv", "-o", out_dir
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        parts = sorted(os.listdir(out_dir))
+        assert parts == ["part-00000.csv", "part-00001.csv", "part-00002.csv"]
+        seen = {}
+        for part in parts:
+            with open(os.path.join(out_dir, part)) as f:
+                for row in csv.DictReader(f):
+                    assert seen.setdefault(row["country"], part) == part
+        assert len(seen) == 7
+    
+    def test_agg_group_by_date(self, temp_dir):
+        """Test agg --group-by-date truncating dates to month buckets"""
+        input_path = os.path.join(temp_dir, "orders.csv")
+        with open(input_path, "w") as f:
+            f.write("day,amount\n2024-01-15,1\n2024-02-03,4\n2024-01-31,2\n")
+        output_path = os.path.join(temp_dir, "monthly.csv")
+        result = subprocess.run([
+            "./target/debug/dpa", "agg", input_path, "--group-by-date", "day:month", "--sum", "amount",
+            "--dtypes", "day=date", "-o", output_path
+        ], capture_output=True, text=True)
+        assert result.returncode == 0
+        with open(output_path) as f:
+            assert f.read().splitlines() == ["day,sum_amount", "2024-01-01,3", "2024-02-01,4"]
+    
     def test_invalid_file(self):
         """Test error handling for invalid file"""
         result = subprocess.run(["./target/debug/dpa", "schema", "nonexistent.csv"], 
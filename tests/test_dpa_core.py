@@ -144,6 +144,44 @@ class TestDPACore:
         # Clean up
         os.remove("dpa_out.parquet")
     
+    def test_sample_py(self, sample_data_path, temp_dir):
+        """Test sample_py function"""
+        output_path = os.path.join(temp_dir, "sample.csv")
+        
+        result = dpa_core.sample_py(sample_data_path, 50, 42, None, output_path)
+        
+        assert result == output_path
+        with open(output_path) as f:
+            assert len(f.read().splitlines()) == 51
+    
+    def test_sample_py_fraction(self, sample_data_path, temp_dir):
+        """Test sample_py with a fraction instead of a size"""
+        output_path = os.path.join(temp_dir, "sample.csv")
+        dpa_core.sample_py(sample_data_path, seed=1, output=output_path, fraction=0.2)
+        with open(output_path) as f:
+            assert len(f.read().splitlines()) == 101
+    
+    def test_split_py(self, sample_data_path, temp_dir):
+        """Test split_py function"""
+        train = os.path.join(temp_dir, "train.parquet")
+        test = os.path.join(temp_dir, "test.parquet")
+        
+        result = dpa_core.split_py(sample_data_path, train, test, 0.25, 1)
+        
+        assert result == (train, test)
+        assert os.path.exists(train)
+        assert os.path.exists(test)
+    
+    def test_kfold_py(self, sample_data_path, temp_dir):
+        """Test kfold_py function"""
+        pairs = dpa_core.kfold_py(sample_data_path, os.path.join(temp_dir, "folds"), 3, 7)
+        assert len(pairs) == 3
+        assert all(os.path.exists(train) and os.path.exists(test) for train, test in pairs)
+        with open(os.path.join(temp_dir, "folds", "notes.txt"), "w") as f:
+            f.write("keep me\n")
+        dpa_core.kfold_py(sample_data_path, os.path.join(temp_dir, "folds"), 2, 7)
+        assert sorted(os.listdir(os.path.join(temp_dir, "folds"))) == ["fold_0", "fold_1", "notes.txt"]
+    
     def test_invalid_file_path(self):
         """Test error handling for invalid file path"""
         with pytest.raises(Exception):
@@ -174,7 +212,8 @@ class TestDPACore:
     
     def test_module_attributes(self):
         """Test that all expected functions are available"""
-        expected_functions = ['filter_py', 'select_py', 'convert_py', 'profile_py']
+        expected_functions = ['filter_py', 'select_py', 'convert_py', 'profile_py',
+                              'sample_py', 'split_py', 'kfold_py', 'validate_py']
         
         for func_name in expected_functions:
             assert hasattr(dpa_core, func_name), f"Missing function: {func_name}"